@@ -0,0 +1,72 @@
+// --benchmark: runs the pipeline over a search saved with --dump-search and reports how long
+// it took instead of saving anything, so rate and concurrency settings can be compared on
+// exactly the same input.
+
+use crate::SearchResult;
+use anyhow::{Result, anyhow};
+use std::fs;
+use std::time::Duration;
+
+// Time spent waiting on each kind of outside service, summed over every paper
+#[derive(Default)]
+pub struct PhaseTimes {
+    // Landing pages
+    pub fetch: Duration,
+    // DOI registries and other metadata APIs
+    pub metadata: Duration,
+    pub llm: Duration,
+}
+
+pub struct Report {
+    pub total: Duration,
+    // Wall time of each process_result call, in processing order
+    pub latencies: Vec<Duration>,
+    pub phases: PhaseTimes,
+}
+
+pub fn save_dump(path: &str, results: &[SearchResult]) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(results)?)?;
+    Ok(())
+}
+
+pub fn load_dump(path: &str) -> Result<Vec<SearchResult>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| anyhow!("could not read search dump {}: {}", path, e))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| anyhow!("{} is not a search dump (write one with --dump-search): {}", path, e))
+}
+
+impl Report {
+    pub fn lines(&self) -> Vec<String> {
+        let papers = self.latencies.len();
+        let secs = self.total.as_secs_f64();
+        let per_second = if secs > 0.0 { papers as f64 / secs } else { 0.0 };
+
+        let mut sorted = self.latencies.clone();
+        sorted.sort();
+        let median = match sorted.len() {
+            0 => Duration::ZERO,
+            n if n % 2 == 1 => sorted[n / 2],
+            n => (sorted[n / 2 - 1] + sorted[n / 2]) / 2,
+        };
+
+        // Parsing, cleaning, pacing sleeps and everything else not spent waiting on a service
+        let busy: Duration = self.latencies.iter().sum();
+        let other = busy.saturating_sub(self.phases.fetch + self.phases.metadata + self.phases.llm);
+        let share = |d: Duration| if busy.is_zero() { 0.0 } else { d.as_secs_f64() / busy.as_secs_f64() * 100.0 };
+
+        let mut lines = vec![
+            format!("Papers: {}", papers),
+            format!("Total time: {:.2}s", secs),
+            format!("Throughput: {:.2} papers/s", per_second),
+            format!("Median latency: {:.0}ms", median.as_secs_f64() * 1000.0),
+            "Phases:".to_string(),
+            // Replaced by the dump, listed so the breakdown reads the same as a live run's
+            "   search:   skipped (read from dump)".to_string(),
+        ];
+        for (name, time) in [("fetch", self.phases.fetch), ("metadata", self.phases.metadata), ("llm", self.phases.llm), ("other", other)] {
+            lines.push(format!("   {:<9} {:>8.2}s  {:>5.1}%", format!("{}:", name), time.as_secs_f64(), share(time)));
+        }
+        lines
+    }
+}
@@ -0,0 +1,83 @@
+// --cache-dir: DOI metadata kept on disk between runs, so a search overlapping an earlier one
+// doesn't spend API quota on papers it has already looked up. One JSON file per DOI, named
+// like the PDFs, with the time it was fetched so --cache-ttl-days can age it out.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::fs;
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    // File names flatten "/", ":" and "_" alike, so the DOI decides whether a file is this one's
+    #[serde(default)]
+    doi: String,
+    fetched_at: DateTime<Utc>,
+    metadata: T,
+}
+
+pub struct MetadataCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl MetadataCache {
+    // None when --cache-dir is empty, which turns caching off
+    pub fn new(dir: &str, ttl_days: u64) -> Option<Self> {
+        if dir.trim().is_empty() {
+            return None;
+        }
+        Some(Self { dir: PathBuf::from(dir.trim()), ttl: Duration::days(ttl_days as i64) })
+    }
+
+    fn path(&self, doi: &str) -> PathBuf {
+        let safe: String = doi.to_lowercase().chars()
+            .map(|c| if c.is_alphanumeric() || matches!(c, '.' | '-' | '(' | ')') { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.json", safe))
+    }
+
+    // Missing, unreadable and expired entries all count as a miss, as does one for another DOI
+    // that maps to the same file
+    pub async fn get<T: DeserializeOwned>(&self, doi: &str) -> Option<T> {
+        let contents = fs::read_to_string(self.path(doi)).await.ok()?;
+        let entry: Entry<T> = serde_json::from_str(&contents).ok()?;
+        (entry.doi.eq_ignore_ascii_case(doi) && Utc::now() - entry.fetched_at < self.ttl).then_some(entry.metadata)
+    }
+
+    // Written to a .part file and renamed, two workers fetching the same DOI can't leave half
+    // a file behind
+    pub async fn put<T: Serialize>(&self, doi: &str, metadata: &T) -> anyhow::Result<()> {
+        fs::create_dir_all(&self.dir).await?;
+        let entry = Entry { doi: doi.to_string(), fetched_at: Utc::now(), metadata };
+        let path = self.path(doi);
+        let partial = PathBuf::from(format!("{}.{}.part", path.display(), fastrand::u32(..)));
+        fs::write(&partial, serde_json::to_string(&entry)?).await?;
+        fs::rename(&partial, &path).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn dois_sharing_a_file_name_do_not_share_metadata() {
+        let dir = std::env::temp_dir().join(format!("researcher-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let cache = MetadataCache::new(&dir.to_string_lossy(), 30).unwrap();
+        assert_eq!(cache.path("10.1000/a_b"), cache.path("10.1000/a:b"));
+
+        cache.put("10.1000/a_b", &"Underscore paper".to_string()).await.unwrap();
+        assert_eq!(cache.get::<String>("10.1000/A_B").await.as_deref(), Some("Underscore paper"));
+        assert_eq!(cache.get::<String>("10.1000/a:b").await, None);
+        assert_eq!(cache.get::<String>("10.1000/a/b").await, None);
+
+        cache.put("10.1000/a/b", &"Slash paper".to_string()).await.unwrap();
+        assert_eq!(cache.get::<String>("10.1000/a/b").await.as_deref(), Some("Slash paper"));
+        assert_eq!(cache.get::<String>("10.1000/a_b").await, None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,88 @@
+// --config: a TOML file of flag values, so a usual setup doesn't have to be retyped.
+// Keys are the long flag names, with dashes or underscores:
+//
+//   subject = "protein folding"
+//   engines = ["arxiv", "pubmed"]   # lists are joined with commas
+//   min_score = 0.75
+//   no_ai = true                    # false just leaves the flag out
+//
+// The file's values go in front of the real arguments, so anything typed on the command
+// line wins and clap validates both the same way.
+
+use crate::Args;
+use anyhow::{Result, anyhow};
+use clap::CommandFactory;
+use std::ffi::OsString;
+use std::fs;
+
+pub fn args_with_config(cli: Vec<OsString>) -> Result<Vec<OsString>> {
+    let Some(path) = config_path(&cli) else {
+        return Ok(cli);
+    };
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| anyhow!("could not read config file {}: {}", path, e))?;
+    let table: toml::Table = toml::from_str(&contents)
+        .map_err(|e| anyhow!("{} is not valid TOML: {}", path, e))?;
+
+    let command = Args::command();
+    let mut from_file = Vec::new();
+    for (key, value) in table {
+        let long = key.replace('_', "-");
+        let arg = command.get_arguments()
+            .find(|a| a.get_long() == Some(long.as_str()) && long != "config")
+            .ok_or_else(|| anyhow!("{}: unknown option '{}'", path, key))?;
+        let flag = format!("--{}", long);
+
+        let value = match value {
+            toml::Value::Boolean(set) if !arg.get_action().takes_values() => {
+                if set {
+                    from_file.push(OsString::from(flag));
+                }
+                continue;
+            }
+            toml::Value::String(s) => s,
+            toml::Value::Array(items) => items.iter()
+                .map(scalar)
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| anyhow!("{}: '{}' can only list strings and numbers", path, key))?
+                .join(","),
+            other => scalar(&other).ok_or_else(|| anyhow!("{}: '{}' can't be a table", path, key))?,
+        };
+        from_file.push(OsString::from(format!("{}={}", flag, value)));
+    }
+
+    // Right after the program name, before any subcommand
+    let mut args = cli;
+    let rest = args.split_off(1.min(args.len()));
+    args.extend(from_file);
+    args.extend(rest);
+    Ok(args)
+}
+
+// Found before clap runs, which needs the file's values to already be in place
+fn config_path(cli: &[OsString]) -> Option<String> {
+    let mut args = cli.iter().skip(1).map(|a| a.to_string_lossy());
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(path.to_string());
+        }
+        if arg == "--config" {
+            return args.next().map(|p| p.to_string());
+        }
+    }
+    None
+}
+
+fn scalar(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(s) => Some(s.clone()),
+        toml::Value::Integer(n) => Some(n.to_string()),
+        toml::Value::Float(f) => Some(f.to_string()),
+        toml::Value::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
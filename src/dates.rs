@@ -0,0 +1,85 @@
+// Publication dates out of metadata. Sources rarely agree on a shape: CrossRef/CSL send
+// "date-parts" arrays that may stop at the year or hold strings and nulls, page meta tags
+// hold anything from "2020" to "2020/05/03" to "May 3, 2020". Missing months and days
+// become the 1st so partial dates still sort and compare.
+
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, NaiveDate};
+use serde_json::Value;
+
+// A CrossRef/CSL date object: {"date-parts": [[2020, 5, 3]]}, with "date-time" or "raw" as fallbacks
+pub fn from_date_parts(value: &Value) -> Option<NaiveDate> {
+    if let Some(parts) = value["date-parts"].get(0).and_then(|p| p.as_array()) {
+        let number = |i: usize| parts.get(i).and_then(|p| {
+            p.as_i64().or_else(|| p.as_str().and_then(|s| s.trim().parse().ok()))
+        });
+        if let Some(date) = number(0).and_then(|year| from_parts(year, number(1), number(2))) {
+            return Some(date);
+        }
+    }
+
+    ["date-time", "raw", "literal"].iter()
+        .find_map(|key| value[*key].as_str().and_then(parse_date))
+}
+
+// First of the given keys holding a usable date object
+pub fn first_date(data: &Value, keys: &[&str]) -> Option<NaiveDate> {
+    keys.iter().find_map(|key| from_date_parts(&data[*key]))
+}
+
+pub fn parse_date(raw: &str) -> Option<NaiveDate> {
+    let raw = raw.trim();
+    let numbers: Vec<&str> = raw.split(|c: char| !c.is_ascii_digit())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    // Year first: 2020, 2020-05, 2020/05/03, 2020-05-03T10:00:00Z
+    if let Some(first) = numbers.first()
+        && first.len() == 4
+        && raw.starts_with(first)
+    {
+        let number = |i: usize| numbers.get(i).and_then(|n| n.parse().ok());
+        return number(0).and_then(|year| from_parts(year, number(1), number(2)));
+    }
+
+    for format in ["%B %d, %Y", "%b %d, %Y", "%d %B %Y", "%d %b %Y", "%b. %d, %Y"] {
+        if let Ok(date) = NaiveDate::parse_from_str(raw, format) {
+            return Some(date);
+        }
+    }
+
+    // "03/05/2020" is ambiguous and "Spring 2020" has no day, keep just the year
+    numbers.iter()
+        .filter(|n| n.len() == 4)
+        .find_map(|n| n.parse().ok().and_then(|year| from_parts(year, None, None)))
+}
+
+fn from_parts(year: i64, month: Option<i64>, day: Option<i64>) -> Option<NaiveDate> {
+    let year = i32::try_from(year).ok().filter(|y| (1000..=9999).contains(y))?;
+    let month = month.and_then(|m| u32::try_from(m).ok()).filter(|m| (1..=12).contains(m));
+    // A day without a month means nothing
+    let day = month.and(day).and_then(|d| u32::try_from(d).ok());
+
+    // An impossible day (Feb 30) falls back to the month rather than dropping the date
+    day.and_then(|d| NaiveDate::from_ymd_opt(year, month.unwrap_or(1), d))
+        .or_else(|| NaiveDate::from_ymd_opt(year, month.unwrap_or(1), 1))
+}
+
+// The absolute bounds of a --time-range: "2020-01-01:2023-12-31" as given, "5year" as the
+// five calendar years ending with this one (the same years --split-by-year searches, and
+// year-only dates near the start stay in). None for SearXNG's own ranges and for no range
+pub fn time_range_bounds(spec: &str, today: NaiveDate) -> Result<Option<(NaiveDate, NaiveDate)>> {
+    let spec = spec.trim();
+    if let Some((start, end)) = spec.split_once(':') {
+        let parse = |s: &str| NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+            .map_err(|_| anyhow!("'{}' in --time-range is not a YYYY-MM-DD date", s.trim()));
+        let (start, end) = (parse(start)?, parse(end)?);
+        if start > end {
+            return Err(anyhow!("--time-range starts after it ends ({} > {})", start, end));
+        }
+        return Ok(Some((start, end)));
+    }
+
+    let years = spec.strip_suffix("year").and_then(|n| n.parse::<i32>().ok()).filter(|n| *n > 0);
+    Ok(years.and_then(|n| NaiveDate::from_ymd_opt(today.year() - n + 1, 1, 1)).map(|start| (start, today)))
+}
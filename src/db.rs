@@ -0,0 +1,102 @@
+// --db: saved papers in a SQLite database instead of results.txt, so an abstract that happens
+// to contain "====" or "DOI: " can't break anything. The common fields get columns for
+// querying, the whole record is kept as JSON next to them.
+
+use crate::results::PaperResult;
+use anyhow::Result;
+use rusqlite::{Connection, params};
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+pub struct ResultsDb {
+    // rusqlite connections can't be shared between threads on their own
+    conn: Mutex<Connection>,
+}
+
+impl ResultsDb {
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        // doi is UNIQUE rather than the primary key so papers without one (stored as NULL)
+        // don't collide, SQLite only lets a primary key hold NULLs as a legacy quirk
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS papers (
+                id INTEGER PRIMARY KEY,
+                doi TEXT UNIQUE,
+                title TEXT NOT NULL,
+                url TEXT NOT NULL,
+                score REAL NOT NULL,
+                abstract TEXT NOT NULL,
+                saved_at TEXT NOT NULL,
+                record TEXT NOT NULL
+            );",
+        )?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    // False when a paper with the same DOI is already stored
+    pub fn insert(&self, paper: &PaperResult) -> Result<bool> {
+        let inserted = self.conn.lock().unwrap().execute(
+            "INSERT OR IGNORE INTO papers (doi, title, url, score, abstract, saved_at, record)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                Self::doi_column(&paper.doi),
+                paper.title,
+                paper.url,
+                paper.score,
+                paper.abstract_text,
+                paper.timestamp,
+                serde_json::to_string(paper)?,
+            ],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    pub fn dois(&self) -> Result<HashSet<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare("SELECT doi FROM papers WHERE doi IS NOT NULL")?;
+        let dois = statement.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+        Ok(dois)
+    }
+
+    // In saving order. `filter` matches title, abstract or DOI, case-insensitively for ASCII
+    pub fn papers(&self, filter: Option<&str>) -> Result<Vec<PaperResult>> {
+        let conn = self.conn.lock().unwrap();
+        let pattern = filter.map(|f| format!("%{}%", f.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")));
+        let mut statement = conn.prepare(
+            "SELECT record FROM papers
+             WHERE ?1 IS NULL
+                OR title LIKE ?1 ESCAPE '\\'
+                OR abstract LIKE ?1 ESCAPE '\\'
+                OR doi LIKE ?1 ESCAPE '\\'
+             ORDER BY id",
+        )?;
+        let records = statement.query_map([pattern], |row| row.get::<_, String>(0))?;
+
+        let mut papers = Vec::new();
+        for record in records {
+            papers.push(serde_json::from_str(&record?)?);
+        }
+        Ok(papers)
+    }
+
+    // How many rows went, 0 when no paper has that DOI
+    pub fn delete(&self, doi: &str) -> Result<usize> {
+        let deleted = self.conn.lock().unwrap().execute("DELETE FROM papers WHERE doi = ?1", [doi.trim()])?;
+        Ok(deleted)
+    }
+
+    pub fn count(&self) -> Result<usize> {
+        let count: i64 = self.conn.lock().unwrap().query_row("SELECT COUNT(*) FROM papers", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        self.conn.lock().unwrap().execute("DELETE FROM papers", [])?;
+        Ok(())
+    }
+
+    fn doi_column(doi: &str) -> Option<&str> {
+        let doi = doi.trim();
+        (!doi.is_empty() && doi != "NA").then_some(doi)
+    }
+}
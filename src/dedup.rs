@@ -0,0 +1,183 @@
+// Duplicate detection: DOIs already handled, plus titles for papers that have no DOI or
+// reach us under two URLs.
+
+use crate::results::normalize_title;
+use anyhow::Result;
+use std::collections::HashSet;
+
+// Processed DOIs and identifier keys ("pmid:123"), in memory for one-shot runs or in an
+// on-disk sled tree (--dedup-db) so a long-lived process doesn't hold every key it has ever
+// seen. sled locks the database to one process while it's open
+pub enum KeyStore {
+    Memory(HashSet<String>),
+    Disk(sled::Tree),
+}
+
+impl KeyStore {
+    pub fn memory(keys: HashSet<String>) -> Self {
+        Self::Memory(keys)
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Memory(keys) => keys.len(),
+            Self::Disk(tree) => tree.len(),
+        }
+    }
+
+    // A failed disk read counts as unseen, processing a paper twice beats dropping it
+    pub fn contains(&self, key: &str) -> bool {
+        match self {
+            Self::Memory(keys) => keys.contains(key),
+            Self::Disk(tree) => tree.contains_key(key).unwrap_or(false),
+        }
+    }
+
+    pub fn insert(&mut self, key: &str) -> Result<()> {
+        match self {
+            Self::Memory(keys) => {
+                keys.insert(key.to_string());
+            }
+            Self::Disk(tree) => {
+                tree.insert(key, &[])?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn clear(&mut self) -> Result<()> {
+        match self {
+            Self::Memory(keys) => keys.clear(),
+            Self::Disk(tree) => {
+                tree.clear()?;
+                tree.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// --dedup-db: DOIs in the default tree (where databases from before the other two kept them),
+// identifier keys and normalized titles in trees of their own
+pub struct DedupDb {
+    pub dois: KeyStore,
+    pub ids: KeyStore,
+    pub titles: sled::Tree,
+}
+
+impl DedupDb {
+    pub fn open(path: &str) -> Result<Self> {
+        let db = sled::open(path)?;
+        Ok(Self {
+            ids: KeyStore::Disk(db.open_tree("ids")?),
+            titles: db.open_tree("titles")?,
+            dois: KeyStore::Disk((*db).clone()),
+        })
+    }
+}
+
+pub struct TitleIndex {
+    titles: Titles,
+    // 1.0 means only identical normalized titles count as duplicates
+    threshold: f64,
+}
+
+enum Titles {
+    Memory { exact: HashSet<String>, titles: Vec<String> },
+    // Normalized titles as keys. A fuzzy lookup reads through the tree instead of a list in memory
+    Disk(sled::Tree),
+}
+
+impl TitleIndex {
+    pub fn new(threshold: f64) -> Self {
+        Self {
+            titles: Titles::Memory { exact: HashSet::new(), titles: Vec::new() },
+            threshold: threshold.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn disk(tree: sled::Tree, threshold: f64) -> Self {
+        Self { titles: Titles::Disk(tree), threshold: threshold.clamp(0.0, 1.0) }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.titles {
+            Titles::Memory { titles, .. } => titles.len(),
+            Titles::Disk(tree) => tree.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&mut self) -> Result<()> {
+        match &mut self.titles {
+            Titles::Memory { exact, titles } => {
+                exact.clear();
+                titles.clear();
+            }
+            Titles::Disk(tree) => {
+                tree.clear()?;
+                tree.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn insert(&mut self, title: &str) -> Result<()> {
+        let normalized = normalize_title(title);
+        if normalized.is_empty() {
+            return Ok(());
+        }
+        match &mut self.titles {
+            Titles::Memory { exact, titles } => {
+                if exact.insert(normalized.clone()) {
+                    titles.push(normalized);
+                }
+            }
+            Titles::Disk(tree) => {
+                tree.insert(normalized.as_str(), &[])?;
+            }
+        }
+        Ok(())
+    }
+
+    // Returns the stored title the candidate duplicates, if any. Disk read errors count as no match
+    pub fn find_similar(&self, title: &str) -> Option<String> {
+        let normalized = normalize_title(title);
+        if normalized.is_empty() {
+            return None;
+        }
+
+        let exact = match &self.titles {
+            Titles::Memory { exact, .. } => exact.contains(&normalized),
+            Titles::Disk(tree) => tree.contains_key(normalized.as_str()).unwrap_or(false),
+        };
+        if exact {
+            return Some(normalized);
+        }
+
+        if self.threshold >= 1.0 {
+            return None;
+        }
+
+        // Normalized Levenshtein can't reach the threshold when the lengths differ by more than
+        // (1 - threshold) of the longer one, which skips almost every stored title cheaply
+        let len = normalized.chars().count();
+        let similar = |t: &str| {
+            let other = t.chars().count();
+            let longest = len.max(other) as f64;
+            (len.abs_diff(other) as f64) <= (1.0 - self.threshold) * longest
+                && strsim::normalized_levenshtein(&normalized, t) >= self.threshold
+        };
+        match &self.titles {
+            Titles::Memory { titles, .. } => titles.iter().find(|t| similar(t)).cloned(),
+            Titles::Disk(tree) => tree.iter()
+                .keys()
+                .filter_map(|key| key.ok())
+                .filter_map(|key| String::from_utf8(key.to_vec()).ok())
+                .find(|t| similar(t)),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+// --score-mode embedding: relevance as the cosine similarity between the subject's embedding
+// and the paper's, instead of asking the model for a score. No prompt to parse and the same
+// paper always gets the same score.
+
+use anyhow::{Result, anyhow};
+use ollama_rs::Ollama;
+use ollama_rs::generation::embeddings::request::GenerateEmbeddingsRequest;
+
+pub async fn embed(ollama: &Ollama, model: &str, text: &str) -> Result<Vec<f32>> {
+    let request = GenerateEmbeddingsRequest::new(model.to_string(), text.into());
+    let response = ollama.generate_embeddings(request).await?;
+    response.embeddings.into_iter().next()
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| anyhow!("{} returned no embedding", model))
+}
+
+// 0.0 for vectors of different lengths or with no direction, rather than NaN
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_vectors_are_fully_similar() {
+        let v = [0.3, -1.2, 4.0];
+        assert!((cosine_similarity(&v, &v) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn orthogonal_vectors_score_zero() {
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 2.0]), 0.0);
+    }
+
+    #[test]
+    fn mismatched_lengths_score_zero() {
+        assert_eq!(cosine_similarity(&[1.0, 2.0], &[1.0, 2.0, 3.0]), 0.0);
+    }
+
+    #[test]
+    fn zero_vector_scores_zero_not_nan() {
+        let score = cosine_similarity(&[0.0, 0.0], &[1.0, 1.0]);
+        assert!(!score.is_nan());
+        assert_eq!(score, 0.0);
+    }
+}
@@ -0,0 +1,70 @@
+// --few-shot-examples: earlier accept/reject decisions shown to the model before the paper
+// it has to score, so its scores drift towards the user's own judgement. The decisions file
+// holds one JSON object per line:
+// {"title": "...", "abstract": "...", "accepted": true, "reason": "...", "subject": "..."}
+// Only title and accepted are required.
+
+use crate::DOIScraper;
+use serde::Deserialize;
+use std::fs;
+
+#[derive(Deserialize)]
+struct Decision {
+    title: String,
+    #[serde(default, rename = "abstract")]
+    abstract_text: String,
+    accepted: bool,
+    #[serde(default)]
+    reason: String,
+    // Decisions made for another topic say little about this one
+    #[serde(default)]
+    subject: String,
+}
+
+// The `count` most recent decisions for these subjects, alternating accepted and rejected so the
+// model sees both sides. Empty when there's no history, which leaves the prompt as it was
+pub fn load_examples(path: &str, subjects: &[String], count: usize) -> String {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return String::new();
+    };
+
+    let decisions: Vec<Decision> = contents.lines()
+        .filter_map(|line| serde_json::from_str::<Decision>(line).ok())
+        .filter(|d| !d.title.trim().is_empty())
+        .filter(|d| d.subject.is_empty() || subjects.iter().any(|s| d.subject.eq_ignore_ascii_case(s)))
+        .collect();
+
+    let mut accepted = decisions.iter().rev().filter(|d| d.accepted).peekable();
+    let mut rejected = decisions.iter().rev().filter(|d| !d.accepted).peekable();
+    let mut picked = Vec::new();
+    while picked.len() < count && (accepted.peek().is_some() || rejected.peek().is_some()) {
+        if let Some(d) = accepted.next() {
+            picked.push(d);
+        }
+        if picked.len() < count
+            && let Some(d) = rejected.next()
+        {
+            picked.push(d);
+        }
+    }
+
+    if picked.is_empty() {
+        return String::new();
+    }
+
+    let mut examples = String::from("Here is how the researcher judged some earlier papers, score in the same spirit:\n\n");
+    for d in picked {
+        examples.push_str(&format!("Paper Title: \"{}\"\n", d.title.trim()));
+        if !d.abstract_text.trim().is_empty() {
+            examples.push_str(&format!("Abstract: \"{}\"\n", DOIScraper::safe_truncate(d.abstract_text.trim(), 300)));
+        }
+        let verdict = if d.accepted { "RELEVANT" } else { "NOT RELEVANT" };
+        if d.reason.trim().is_empty() {
+            examples.push_str(&format!("Decision: {}\n\n", verdict));
+        } else {
+            examples.push_str(&format!("Decision: {} ({})\n\n", verdict, d.reason.trim()));
+        }
+    }
+    examples.push_str("Now the paper to evaluate.\n\n");
+    examples
+}
@@ -0,0 +1,100 @@
+// JSON Lines records for --format jsonl: one compact object per saved paper, so the output
+// can be piped into jq or anything else that reads a line at a time. The keys are the block
+// format's labels, not PaperResult's field names.
+
+use crate::results::PaperResult;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Record {
+    doi: String,
+    title: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    authors: Vec<String>,
+    url: String,
+    score: f32,
+    #[serde(rename = "abstract")]
+    abstract_text: String,
+    saved: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    reason: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    subject: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    keywords: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cited_by: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    published: Option<NaiveDate>,
+    #[serde(rename = "type", skip_serializing_if = "String::is_empty")]
+    resource_type: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    engine: String,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    identifiers: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    oa_url: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    dead: bool,
+}
+
+pub fn format_record(paper: &PaperResult) -> String {
+    let record = Record {
+        doi: paper.doi.clone(),
+        title: paper.title.clone(),
+        authors: paper.authors.clone(),
+        url: paper.url.clone(),
+        score: paper.score,
+        abstract_text: paper.abstract_text.clone(),
+        saved: paper.timestamp.clone(),
+        reason: paper.reason.clone(),
+        subject: paper.subject.clone(),
+        keywords: paper.keywords.clone(),
+        cited_by: paper.cited_by,
+        published: paper.published,
+        resource_type: paper.resource_type.clone(),
+        engine: paper.engine.clone(),
+        identifiers: paper.identifiers.clone(),
+        oa_url: paper.oa_url.clone(),
+        dead: paper.dead,
+    };
+    // Serializing plain strings and numbers can't fail
+    format!("{}\n", serde_json::to_string(&record).unwrap_or_default())
+}
+
+// Neither the block format nor RIS starts with a brace
+pub fn looks_like_jsonl(contents: &str) -> bool {
+    contents.lines()
+        .find(|l| !l.trim().is_empty())
+        .is_some_and(|l| l.trim_start().starts_with('{'))
+}
+
+// A line cut short by a crash is skipped, the rest of the file still reads
+pub fn parse(contents: &str) -> Vec<PaperResult> {
+    contents.lines()
+        .filter_map(|line| serde_json::from_str::<Record>(line.trim()).ok())
+        .map(|r| PaperResult {
+            doi: if r.doi.trim().is_empty() { "NA".to_string() } else { r.doi },
+            title: r.title,
+            authors: r.authors,
+            url: r.url,
+            score: r.score,
+            reason: r.reason,
+            subject: r.subject,
+            abstract_text: r.abstract_text,
+            keywords: r.keywords,
+            cited_by: r.cited_by,
+            published: r.published,
+            resource_type: r.resource_type,
+            engine: r.engine,
+            identifiers: r.identifiers,
+            oa_url: r.oa_url,
+            timestamp: r.saved,
+            dead: r.dead,
+        })
+        .filter(|paper| paper.score > 0.0)
+        .collect()
+}
@@ -0,0 +1,39 @@
+// --lang: drops papers whose abstract is confidently in another language. Detection is
+// whatlang's trigram guess, which needs a sentence or two to go on, so short or ambiguous
+// text is always kept.
+
+use anyhow::{Result, anyhow};
+use whatlang::Lang;
+
+// Below this many characters whatlang's guesses are mostly noise
+const MIN_CHARS: usize = 40;
+
+// The two-letter codes people will actually type, whatlang itself only knows ISO 639-3
+const TWO_LETTER: &[(&str, &str)] = &[
+    ("en", "eng"), ("de", "deu"), ("fr", "fra"), ("es", "spa"), ("it", "ita"), ("pt", "por"),
+    ("nl", "nld"), ("ru", "rus"), ("zh", "cmn"), ("ja", "jpn"), ("ko", "kor"), ("pl", "pol"),
+    ("sv", "swe"), ("da", "dan"), ("fi", "fin"), ("tr", "tur"), ("uk", "ukr"), ("cs", "ces"),
+    ("ar", "ara"), ("fa", "pes"), ("hi", "hin"), ("id", "ind"), ("vi", "vie"), ("el", "ell"),
+];
+
+// "en,de", "eng" or "English", any mix of them
+pub fn parse_languages(spec: &str) -> Result<Vec<Lang>> {
+    spec.split(',')
+        .map(|code| code.trim().to_lowercase())
+        .filter(|code| !code.is_empty())
+        .map(|code| {
+            let three = TWO_LETTER.iter().find(|(two, _)| *two == code).map_or(code.as_str(), |(_, three)| three);
+            Lang::from_code(three)
+                .or_else(|| Lang::all().iter().copied().find(|l| l.eng_name().eq_ignore_ascii_case(&code)))
+                .ok_or_else(|| anyhow!("unknown language '{}' for --lang", code))
+        })
+        .collect()
+}
+
+// The detected language and how sure whatlang is of it, None when the text is too short
+pub fn detect(text: &str) -> Option<(Lang, f64)> {
+    if text.chars().count() < MIN_CHARS {
+        return None;
+    }
+    whatlang::detect(text).map(|info| (info.lang(), info.confidence()))
+}
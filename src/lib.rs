@@ -0,0 +1,4170 @@
+// ┬─┐┌─┐┌─┐┌─┐┌─┐┬─┐┌─┐┬ ┬┌─┐┬─┐
+// ├┬┘├┤ └─┐├┤ ├─┤├┬┘│  ├─┤├┤ ├┬┘
+// ┴└─└─┘└─┘└─┘┴ ┴┴└─└─┘┴ ┴└─┘┴└─
+
+// Requires Ollama & SearXNG
+// validates relevance with AI, and saves results to a text file.
+
+// Copyright 2025 Servus Altissimi (Pseudonym)
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy of this software and associated documentation files (the "Software"), to deal in the Software without restriction, including without limitation the rights to use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of the Software, and to permit persons to whom the Software is furnished to do so, subject to the following conditions:
+// The above copyright notice and this permission notice shall be included in all copies or substantial portions of the Software.
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+mod bench;
+mod cache;
+pub mod config;
+mod dates;
+mod db;
+mod dedup;
+mod embedding;
+mod feedback;
+mod jsonl;
+mod language;
+pub mod linkcheck;
+pub mod logs;
+mod markdown;
+mod notion;
+mod pdf;
+mod queue;
+pub mod ratelimit;
+pub mod results;
+mod retry;
+mod ris;
+mod search;
+mod spreadsheet;
+mod template;
+mod useragent;
+pub mod web;
+
+use anyhow::{Result, anyhow};
+use bench::PhaseTimes;
+use cache::MetadataCache;
+use chrono::{Datelike, NaiveDate};
+use clap::{Parser, Subcommand};
+use futures::{StreamExt, stream};
+use db::ResultsDb;
+use dedup::{DedupDb, KeyStore, TitleIndex};
+use logs::{LogLevel, Logger};
+use notion::NotionExporter;
+use ollama_rs::Ollama;
+use ollama_rs::generation::completion::request::GenerationRequest;
+use ollama_rs::generation::parameters::FormatType;
+use queue::WorkQueue;
+use ratelimit::{DomainRateLimiter, RateLimiter};
+use regex::Regex;
+use reqwest::Client;
+use results::{MergeStrategy, PaperResult, SortBy};
+use retry::retry_request;
+use scraper::{Html, Selector};
+use search::Searcher;
+use template::TemplateWriter;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Instant;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio::time::Duration;
+
+static PARAGRAPH_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)</(jats:)?p>|<br\s*/?>").unwrap());
+static HEADING_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?is)<(jats:)?title[^>]*>.*?</(jats:)?title>").unwrap());
+static ANY_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]+>").unwrap());
+
+// Held while a record is appended. The web UI can run several searches into the same file
+// (--max-web-searches) and a block must never land in the middle of another
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+// CL arguments for config
+#[derive(Parser, Debug, Clone)]
+// A flag given twice keeps the last value, which is how command-line flags override --config
+#[command(author, version, about = "SearXNG Scientific DOI Scraper with AI Validation", long_about = None, args_override_self = true)]
+pub struct Args {
+    // Read by config.rs before clap parses anything, declared here so it shows in --help
+    #[arg(long, default_value = "", help = "TOML file of flag values (subject = \"...\", no_ai = true, ...). Flags given on the command line take precedence over the file")]
+    pub config: String,
+
+    // Several topics can be given comma-separated ("protein folding, cryo-em"), each is
+    // searched in turn and saved papers note which one they matched
+    #[arg(short, long, default_value = "machine learning")]
+    pub subject: String,
+
+    // A file with one subject per line, searched in turn into the same output in place of
+    // --subject. Blank lines and lines starting with # are skipped. Lines aren't split on commas
+    #[arg(long, default_value = "")]
+    pub query_file: String,
+
+    // --query-file's lines, read once in main
+    #[arg(skip)]
+    pub queries: Vec<String>,
+
+    #[arg(short, long, default_value = "https://searxng.site/")]
+    pub instance: String,
+
+    #[arg(short, long, default_value = "50")]
+    pub max_results: usize,
+
+    // Upper bound on SearXNG result pages requested per search, whatever --max-results asks for
+    #[arg(long, default_value_t = 10)]
+    pub max_pages: usize,
+
+    #[arg(short, long, default_value = "results.txt")]
+    pub output: String,
+
+    #[arg(long, default_value = "llama3.2:latest")]
+    pub model: String,
+
+    // File with the prompt sent to --model in place of the built-in ones, {subject}, {title}
+    // and {abstract} are filled in. It should still ask for {"score": .., "reason": ".."}
+    #[arg(long, default_value = "")]
+    pub prompt_template: String,
+
+    #[arg(long, default_value_t = false)]
+    pub no_ai: bool,
+
+    // day, week, month, year, Nyear ("5year") or exact dates ("2020-01-01:2023-12-31")
+    #[arg(short, long, default_value = "")]
+    pub time_range: String,
+
+    // Publication years to keep, both ends included. Applied to the year the metadata gives,
+    // unlike --time-range's SearXNG part which goes by when a page was indexed
+    #[arg(long)]
+    pub since_year: Option<i32>,
+
+    #[arg(long)]
+    pub until_year: Option<i32>,
+
+    // Keep papers without a publication date when --since-year or --until-year is set
+    #[arg(long, default_value_t = false)]
+    pub keep_undated: bool,
+
+    #[arg(short, long, default_value = "science")]
+    pub category: String,
+
+    #[arg(short, long, default_value = "arxiv,pubmed,google scholar,crossref,openairepublications,openairedatasets,semantic scholar")]
+    pub engines: String,
+
+    #[arg(long, default_value = "0.6")]
+    pub min_score: f32,
+
+    // Same as --log-level debug
+    #[arg(short, long, default_value_t = false)]
+    pub verbose: bool,
+
+    // Same as --log-level error
+    #[arg(short, long, default_value_t = false)]
+    pub quiet: bool,
+
+    // Overrides --verbose and --quiet. info when none of the three is given
+    #[arg(long, value_enum)]
+    pub log_level: Option<LogLevel>,
+
+    #[arg(long, default_value = "6601")]
+    pub web_poort: u16,
+
+    // Start the web interface even though other flags were given (they become its defaults)
+    #[arg(long, default_value_t = false)]
+    pub web: bool,
+
+    #[arg(long, default_value = "http://localhost:11434")]
+    pub ollama_url: String,
+
+    // How many searches the web UI may run at once, extra requests are turned away
+    #[arg(long, default_value = "1")]
+    pub max_web_searches: usize,
+
+    // Dotted path to the results array, for instances that don't use top-level `results`
+    #[arg(long, default_value = "")]
+    pub results_json_path: String,
+
+    // Minimum gap between DOI API requests, shared by every search in the process
+    #[arg(long, default_value = "200")]
+    pub api_interval_ms: u64,
+
+    // Minimum gap between two requests to the same host (publisher pages, PDFs, doi.org and
+    // the metadata APIs). Different hosts don't wait on each other
+    #[arg(long, default_value = "500")]
+    pub rate_limit_ms: u64,
+
+    // Only keep papers Unpaywall knows a free copy of (arXiv papers always count). Needs --unpaywall-email
+    #[arg(long, default_value_t = false)]
+    pub open_access_only: bool,
+
+    // Contact address Unpaywall requires with every request. When set the free copy's URL is
+    // looked up and saved even without --open-access-only
+    #[arg(long, default_value = "")]
+    pub unpaywall_email: String,
+
+    // Save the free PDF of every accepted paper (Unpaywall's copy with --unpaywall-email, or arXiv)
+    #[arg(long, default_value_t = false)]
+    pub download_pdf: bool,
+
+    #[arg(long, default_value = "pdfs/")]
+    pub pdf_dir: String,
+
+    // CORE API key (free from core.ac.uk). With one, CORE's repository copies are asked for
+    // abstracts the registries and Semantic Scholar don't have
+    #[arg(long, default_value = "")]
+    pub core_api_key: String,
+
+    // Semantic Scholar API key, sent as x-api-key. Anonymous requests share a strict rate limit
+    #[arg(long, default_value = "")]
+    pub s2_api_key: String,
+
+    // Integration token for pushing saved papers into a Notion database
+    #[arg(long, default_value = "")]
+    pub notion_token: String,
+
+    #[arg(long, default_value = "")]
+    pub notion_database_id: String,
+
+    // Papers whose AI response couldn't be parsed (refusals etc.) land here instead of the output
+    #[arg(long, default_value = "needs_review.txt")]
+    pub review_output: String,
+
+    // Stop calling the LLM after this many calls (0 = unlimited), remaining papers go to review
+    #[arg(long, default_value = "0")]
+    pub max_llm_calls: usize,
+
+    // Reprocess whatever the queue file says a crashed run never finished
+    #[arg(long, default_value_t = false)]
+    pub resume: bool,
+
+    #[arg(long, default_value = ".researcher_queue.jsonl")]
+    pub queue_file: String,
+
+    // Comma-separated terms, a paper needs at least one of them before it's sent to the AI
+    #[arg(long, default_value = "")]
+    pub require_keywords: String,
+
+    // Match --require-keywords against title/abstract text, or the paper's own keyword metadata
+    #[arg(long, value_enum, default_value = "text")]
+    pub keyword_source: KeywordSource,
+
+    // Keep a list of DOIs the AI rejected and skip them on later runs
+    #[arg(long, default_value_t = false)]
+    pub remember_rejections: bool,
+
+    // Wipe the remembered rejections before starting
+    #[arg(long, default_value_t = false)]
+    pub forget_rejections: bool,
+
+    #[arg(long, default_value = ".researcher_rejected")]
+    pub rejections_file: String,
+
+    // Treat --instance as a comma-separated list, query them all at once and pool the results
+    #[arg(long, default_value_t = false)]
+    pub merge_instances: bool,
+
+    // Rewrite the output file in this order once the run finishes
+    #[arg(long, value_enum, default_value = "none")]
+    pub sort_by: SortBy,
+
+    // Skip papers whose normalized title is at least this similar to one already saved (1.0 = exact match only)
+    #[arg(long, default_value_t = 1.0)]
+    pub title_dedup_threshold: f64,
+
+    // Append to the output file, or start it fresh each run
+    #[arg(long, value_enum, default_value = "append")]
+    pub output_mode: OutputMode,
+
+    // With --output-mode overwrite, also forget the DOIs and titles loaded from the old file
+    #[arg(long, default_value_t = false)]
+    pub reset_dedup: bool,
+
+    // What --subject is: a research topic, an author's name, or the title of a known paper
+    #[arg(long, value_enum, default_value = "topic")]
+    pub search_mode: SearchMode,
+
+    // Start new output files with a UTF-8 byte order mark, for Windows tools that guess the encoding otherwise
+    #[arg(long, default_value_t = false)]
+    pub output_bom: bool,
+
+    // When the model's answer has no score in it, ask once more for just the JSON object
+    #[arg(long, default_value_t = false)]
+    pub retry_unparsed: bool,
+
+    // Per engine, how far a search snippet can stand in for the abstract: "arxiv=high,default=low".
+    // high keeps the snippet, low always enriches from the page/APIs, unlisted engines get the usual length check
+    #[arg(long, default_value = "")]
+    pub snippet_trust: String,
+
+    // An abstract shorter than this many characters sends the scraper to the page and the DOI APIs
+    // for a longer one. Lower it for fields where abstracts are short
+    #[arg(long, default_value_t = 100)]
+    pub min_abstract_chars: usize,
+
+    // Abstracts ending in "..." or cut off at a round length get one more enrichment attempt before scoring
+    #[arg(long, default_value_t = false)]
+    pub complete_truncated_abstracts: bool,
+
+    // Shell command run after each save, with the paper as JSON on stdin
+    #[arg(long, default_value = "")]
+    pub on_save_command: String,
+
+    // Stop the run when the --on-save-command exits non-zero, instead of logging and carrying on
+    #[arg(long, default_value_t = false)]
+    pub hook_fatal: bool,
+
+    // With a multi-year --time-range like 5year, run one search per year instead of falling back to "year"
+    #[arg(long, default_value_t = false)]
+    pub split_by_year: bool,
+
+    // Send every request through this proxy, http://host:port or socks5://host:port. Without it
+    // the HTTPS_PROXY/ALL_PROXY environment variables are used when set
+    #[arg(long, default_value = "")]
+    pub proxy: String,
+
+    // Send this User-Agent on every search and page fetch instead of rotating through a pool of
+    // browser ones
+    #[arg(long, default_value = "")]
+    pub user_agent: String,
+
+    // Idle connections kept open per host (CrossRef, doi.org, ...), unlimited when not given
+    #[arg(long)]
+    pub pool_max_idle_per_host: Option<usize>,
+
+    // How long an idle pooled connection is kept before closing it
+    #[arg(long, default_value_t = 90)]
+    pub pool_idle_timeout_secs: u64,
+
+    // Drop results whose metadata says they're datasets
+    #[arg(long, default_value_t = false, conflicts_with = "only_datasets")]
+    pub exclude_datasets: bool,
+
+    // Keep only datasets, drop articles and everything else
+    #[arg(long, default_value_t = false)]
+    pub only_datasets: bool,
+
+    // Seconds to wait for a publisher page before giving up on it
+    #[arg(long, default_value_t = 15)]
+    pub page_timeout: u64,
+
+    // Seconds to wait for doi.org and the metadata APIs (CrossRef, DataCite, OpenAlex, ...)
+    #[arg(long, default_value_t = 10)]
+    pub api_timeout: u64,
+
+    // Base URL of the CrossRef API, for a mirror or a stand-in server (the integration tests use one)
+    #[arg(long, default_value = "https://api.crossref.org")]
+    pub crossref_url: String,
+
+    // Base URL doi.org content negotiation goes to, replaceable like --crossref-url
+    #[arg(long, default_value = "https://doi.org")]
+    pub doi_org_url: String,
+
+    // Base URL of the DataCite API, replaceable like --crossref-url
+    #[arg(long, default_value = "https://api.datacite.org")]
+    pub datacite_url: String,
+
+    // Looked-up DOI metadata is kept here between runs, one JSON file per DOI. Empty turns the
+    // cache off
+    #[arg(long, default_value = ".cache/")]
+    pub cache_dir: String,
+
+    // Cached metadata older than this is fetched again
+    #[arg(long, default_value_t = 30)]
+    pub cache_ttl_days: u64,
+
+    // Seconds to wait for a SearXNG results page, busy public instances can be slow
+    #[arg(long, default_value_t = 30)]
+    pub search_timeout: u64,
+
+    // Extra attempts after a connection error, timeout, 429 or 5xx from SearXNG or a metadata API,
+    // waiting 500ms, 1s, 2s... or whatever a 429's Retry-After asks for
+    #[arg(long, default_value_t = 3)]
+    pub max_retries: u32,
+
+    // Extra attempts when an instance answers with an HTML page (captcha, rate limit) instead of JSON
+    #[arg(long, default_value_t = 2)]
+    pub searxng_retries: u32,
+
+    // After the run, dump every paper in the output file with all of its fields to this JSON file
+    #[arg(long, default_value = "")]
+    pub export_full: String,
+
+    // After the run, write its counts and timing to this JSON file for scripts to collect
+    #[arg(long, default_value = "")]
+    pub summary_json: String,
+
+    // Lower --min-score by this much for papers that two or more engines returned
+    #[arg(long, default_value_t = 0.0)]
+    pub multi_engine_bonus: f32,
+
+    // Handlebars template rendered for every saved paper, appended to --template-output
+    #[arg(long, default_value = "")]
+    pub template_file: String,
+
+    #[arg(long, default_value = "report.txt")]
+    pub template_output: String,
+
+    // Start processing results as each search pass (year, page) comes back instead of waiting for all of them
+    #[arg(long, default_value_t = false)]
+    pub stream_results: bool,
+
+    // Take --max-results round-robin across the engines instead of in SearXNG's order, so the
+    // fastest engine can't crowd out the rest. Paging goes on to twice --max-results to give
+    // the quieter engines something to pick from. Not with --stream-results
+    #[arg(long, default_value_t = false)]
+    pub balance_engines: bool,
+
+    // What to do when neither the search result nor any metadata source gave a title
+    #[arg(long, value_enum, default_value = "skip")]
+    pub on_missing_title: MissingTitle,
+
+    // Which earlier saves count as duplicates
+    #[arg(long, value_enum, default_value = "persistent")]
+    pub dedup_scope: DedupScope,
+
+    // Keep processed DOIs, titles and identifiers in an on-disk sled database at this path instead
+    // of in memory, for long-lived processes. Filled from the output file the first time, only
+    // used with the persistent dedup scope
+    #[arg(long, default_value = "")]
+    pub dedup_db: String,
+
+    // When saved papers are forced out of the OS cache onto disk
+    #[arg(long, value_enum, default_value = "never")]
+    pub fsync: FsyncPolicy,
+
+    // How often --fsync interval syncs the output files
+    #[arg(long, default_value_t = 30)]
+    pub fsync_interval_secs: u64,
+
+    // Show the model this many past accept/reject decisions from --decisions-file as examples
+    #[arg(long, default_value_t = 0)]
+    pub few_shot_examples: usize,
+
+    // JSON lines of past decisions, see feedback.rs for the fields
+    #[arg(long, default_value = ".researcher_decisions.jsonl")]
+    pub decisions_file: String,
+
+    // How saved papers are written to --output. The review file always uses the text blocks
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: OutputFormat,
+
+    // Results processed at the same time
+    #[arg(long, default_value_t = 4)]
+    pub concurrency: usize,
+
+    // Save papers into this SQLite database instead of --output
+    #[arg(long, default_value = "")]
+    pub db: String,
+
+    // How papers are scored. Embedding similarities run lower than the model's scores, so
+    // --min-score usually needs lowering with it
+    #[arg(long, value_enum, default_value = "llm")]
+    pub score_mode: ScoreMode,
+
+    // Ollama model used by --score-mode embedding
+    #[arg(long, default_value = "nomic-embed-text")]
+    pub embedding_model: String,
+
+    // Comma-separated article types to keep ("journal-article,research-article"), matched
+    // against the Type line without regard to case, spaces or underscores
+    #[arg(long, default_value = "")]
+    pub include_types: String,
+
+    // Comma-separated article types to drop ("editorial,comment,letter")
+    #[arg(long, default_value = "")]
+    pub exclude_types: String,
+
+    // Comma-separated domains whose results are dropped unfetched, subdomains included
+    // ("sciencedirect.com" also covers "www.sciencedirect.com")
+    #[arg(long, default_value = "")]
+    pub exclude_domains: String,
+
+    // Only keep papers whose abstract is in one of these languages ("en", "en,de", "eng" or
+    // "English"). Abstracts too short or too mixed to tell are kept
+    #[arg(long, default_value = "")]
+    pub lang: String,
+
+    // How sure the language detection has to be (0-1) before --lang drops a paper
+    #[arg(long, default_value_t = 0.5)]
+    pub lang_min_confidence: f64,
+
+    // Drop papers whose type couldn't be determined while a type filter is set
+    #[arg(long, default_value_t = false)]
+    pub strict_type: bool,
+
+    // Save the search results to this JSON file before processing them, for --benchmark
+    #[arg(long, default_value = "")]
+    pub dump_search: String,
+
+    // Search and score as usual but save nothing, the papers that would have been saved are
+    // listed at the end. The results file, rejections, work queue and PDF folder are left alone
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+
+    // Process a --dump-search file and report throughput and phase timings. Nothing is saved
+    // and the results file, rejections and work queue are left alone
+    #[arg(long, default_value = "")]
+    pub benchmark: String,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Args {
+    pub fn log_level(&self) -> LogLevel {
+        match (self.log_level, self.quiet, self.verbose) {
+            (Some(level), _, _) => level,
+            (None, true, _) => LogLevel::Error,
+            (None, false, true) => LogLevel::Debug,
+            (None, false, false) => LogLevel::Info,
+        }
+    }
+
+    pub fn load_query_file(&mut self) -> Result<()> {
+        if self.query_file.trim().is_empty() {
+            return Ok(());
+        }
+        let contents = fs::read_to_string(self.query_file.trim())
+            .map_err(|e| anyhow!("Could not read --query-file {}: {}", self.query_file, e))?;
+        self.queries = contents.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        if self.queries.is_empty() {
+            return Err(anyhow!("--query-file {} has no queries in it", self.query_file));
+        }
+        Ok(())
+    }
+
+    // Only topics are split, a wanted title or an author's "Family, Given" can contain commas
+    pub fn subjects(&self) -> Vec<String> {
+        if !self.queries.is_empty() {
+            return self.queries.clone();
+        }
+        if self.search_mode != SearchMode::Topic {
+            return vec![self.subject.trim().to_string()];
+        }
+        let subjects: Vec<String> = self.subject.split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if subjects.is_empty() { vec![self.subject.trim().to_string()] } else { subjects }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum KeywordSource {
+    Text,
+    Metadata,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum SearchMode {
+    Topic,
+    Author,
+    Title,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SnippetTrust {
+    Low,
+    Normal,
+    High,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    // The results.txt blocks
+    Text,
+    // RIS records for EndNote/Zotero import
+    Ris,
+    // One JSON object per line, for jq and other pipelines
+    Jsonl,
+    // A header row and one row per paper, for spreadsheets
+    Csv,
+    // A report to read on GitHub or in Obsidian, one section per paper
+    Markdown,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ScoreMode {
+    // Ask --model for a score and reason
+    Llm,
+    // Cosine similarity of subject and title+abstract embeddings from --embedding-model
+    Embedding,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum FsyncPolicy {
+    // Leave it to the OS
+    Never,
+    // After every saved paper, nothing saved is lost but every save waits on the disk
+    PerPaper,
+    // Every --fsync-interval-secs from a background task, a crash loses at most that much
+    Interval,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum DedupScope {
+    // Only papers saved earlier in this run
+    Run,
+    // Everything already in the output file as well
+    Persistent,
+    // Nothing, the same paper can be saved again and again
+    None,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum MissingTitle {
+    Skip,
+    // Build a stand-in from the last path segment of the URL
+    UrlSlug,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputMode {
+    Append,
+    Overwrite,
+}
+
+// Utilities that work on existing results files instead of searching
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    // Combine several results files into one in the first file's format, dropping duplicates
+    Merge {
+        #[arg(required = true, num_args = 2..)]
+        inputs: Vec<String>,
+
+        #[arg(short, long, default_value = "merged.txt")]
+        output: String,
+
+        // Which entry wins when the same paper appears more than once
+        #[arg(long, value_enum, default_value = "score")]
+        merge_strategy: MergeStrategy,
+    },
+    // Re-apply a different --min-score to an existing results file, no searching or LLM calls
+    Filter {
+        #[arg(default_value = "results.txt")]
+        input: String,
+
+        #[arg(short, long, default_value = "filtered.txt")]
+        output: String,
+
+        #[arg(long, default_value_t = 0.7)]
+        min_score: f32,
+    },
+    // Re-request every saved URL and DOI and report the ones that no longer resolve
+    CheckLinks {
+        #[arg(default_value = "results.txt")]
+        input: String,
+
+        // Links checked at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+
+        // Minimum gap between requests, across all concurrent checks
+        #[arg(long, default_value_t = 250)]
+        interval_ms: u64,
+
+        // Rewrite the input with a "Status: DEAD" line on papers where nothing resolves
+        #[arg(long, default_value_t = false)]
+        annotate: bool,
+
+        // Also write the list of broken links to this file
+        #[arg(long, default_value = "")]
+        report: String,
+    },
+}
+
+// Represents one search result from SearXNG
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub engine: String,
+    // Every engine SearXNG merged into this result, `engine` is just the first of them
+    #[serde(default)]
+    pub engines: Vec<String>,
+    // Which instances returned this result, only filled in with --merge-instances
+    #[serde(default)]
+    pub instances: Vec<String>,
+    // The --subject entry whose search found it, set by the searcher
+    #[serde(default)]
+    pub subject: String,
+}
+
+impl SearchResult {
+    fn url_key(&self) -> String {
+        self.url.trim_end_matches('/').to_string()
+    }
+
+    fn engine_count(&self) -> usize {
+        let mut engines: HashSet<&str> = self.engines.iter().map(|e| e.as_str()).collect();
+        if !self.engine.is_empty() {
+            engines.insert(&self.engine);
+        }
+        engines.len()
+    }
+
+    // The same URL came back again (another instance, another year's search), keep track of
+    // every engine and instance that returned it
+    fn absorb(&mut self, other: &SearchResult) {
+        for engine in other.engines.iter().chain(std::iter::once(&other.engine)) {
+            if !engine.is_empty() && !self.engines.contains(engine) {
+                self.engines.push(engine.clone());
+            }
+        }
+        for instance in &other.instances {
+            if !self.instances.contains(instance) {
+                self.instances.push(instance.clone());
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CrossRefResponse {
+    message: CrossRefMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrossRefMessage {
+    #[serde(rename = "DOI")]
+    #[allow(dead_code)]
+    doi: String,
+    title: Vec<String>,
+    #[serde(default)]
+    #[serde(rename = "abstract")]
+    abstract_text: Option<String>,
+    #[serde(default)]
+    subject: Vec<String>,
+    #[serde(default)]
+    #[serde(rename = "is-referenced-by-count")]
+    cited_by: Option<u32>,
+    // Date objects, read with dates::from_date_parts
+    #[serde(default)]
+    published: serde_json::Value,
+    #[serde(default)]
+    issued: serde_json::Value,
+    #[serde(default)]
+    #[serde(rename = "published-online")]
+    published_online: serde_json::Value,
+    #[serde(default)]
+    #[serde(rename = "published-print")]
+    published_print: serde_json::Value,
+    // "journal-article", "dataset", "posted-content", ...
+    #[serde(default)]
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    author: Vec<CrossRefAuthor>,
+}
+
+// People have given and family (sometimes only family), organisations just a name
+#[derive(Debug, Deserialize)]
+struct CrossRefAuthor {
+    #[serde(default)]
+    given: Option<String>,
+    #[serde(default)]
+    family: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataCiteResponse {
+    data: DataCiteData,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataCiteData {
+    attributes: DataCiteAttributes,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataCiteAttributes {
+    #[serde(default)]
+    titles: Vec<DataCiteTitle>,
+    #[serde(default)]
+    descriptions: Vec<DataCiteDescription>,
+    #[serde(default)]
+    subjects: Vec<DataCiteSubject>,
+    #[serde(default)]
+    dates: Vec<DataCiteDate>,
+    // A number in most records, a string in some older ones
+    #[serde(default)]
+    #[serde(rename = "publicationYear")]
+    publication_year: serde_json::Value,
+    #[serde(default)]
+    types: DataCiteTypes,
+    #[serde(default)]
+    creators: Vec<DataCiteCreator>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataCiteCreator {
+    // "Family, Given" for people
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    #[serde(rename = "givenName")]
+    given_name: Option<String>,
+    #[serde(default)]
+    #[serde(rename = "familyName")]
+    family_name: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DataCiteTypes {
+    // "Dataset", "Text", "Software", ...
+    #[serde(default)]
+    #[serde(rename = "resourceTypeGeneral")]
+    resource_type_general: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataCiteTitle {
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataCiteDescription {
+    description: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataCiteSubject {
+    subject: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataCiteDate {
+    date: String,
+    #[serde(default)]
+    #[serde(rename = "dateType")]
+    date_type: String,
+}
+
+// Whatever a page or DOI API told us about a paper, empty fields mean it didn't say
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Metadata {
+    title: String,
+    abstract_text: String,
+    doi: Option<String>,
+    keywords: Vec<String>,
+    cited_by: Option<u32>,
+    published: Option<NaiveDate>,
+    // Whatever vocabulary the source uses, compare with is_dataset_type
+    resource_type: String,
+    // PMID, PMCID, arXiv ID, ... whichever the source mentions, see add_identifier
+    identifiers: HashMap<String, String>,
+    // "Given Family", in the source's order
+    authors: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ScientificPaper {
+    pub title: String,
+    pub url: String,
+    pub doi: Option<String>,
+    pub authors: Vec<String>,
+    pub abstract_text: String,
+    pub keywords: Vec<String>,
+    pub cited_by: Option<u32>,
+    pub published: Option<NaiveDate>,
+    pub resource_type: String,
+    pub engine: String,
+    pub identifiers: HashMap<String, String>,
+    // Free copy found by Unpaywall, the PDF when it has one
+    pub oa_url: Option<String>,
+    // Which of the run's subjects it was found and scored for
+    pub subject: String,
+    pub relevance_score: f32,
+    pub reason: String,
+    pub needs_review: bool,
+}
+
+impl ScientificPaper {
+    // The on-disk record, stamped with the current time
+    fn to_result(&self) -> PaperResult {
+        PaperResult {
+            doi: self.doi.clone().unwrap_or_else(|| "NA".to_string()),
+            title: self.title.clone(),
+            authors: self.authors.clone(),
+            url: self.url.clone(),
+            score: self.relevance_score,
+            reason: self.reason.clone(),
+            subject: self.subject.clone(),
+            abstract_text: self.abstract_text.clone(),
+            keywords: self.keywords.clone(),
+            cited_by: self.cited_by,
+            published: self.published,
+            resource_type: self.resource_type.clone(),
+            engine: self.engine.clone(),
+            identifiers: self.identifiers.clone(),
+            oa_url: self.oa_url.clone(),
+            timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            dead: false,
+        }
+    }
+}
+
+// Post-save work for papers already written, see handle_outcome
+type AfterSave<'a> = stream::FuturesUnordered<Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>>;
+
+#[derive(Default)]
+struct RunStats {
+    processed: usize,
+    validated: usize,
+    saved: usize,
+    skipped: usize,
+    needs_review: usize,
+}
+
+// --summary-json, RunStats plus when the run happened
+#[derive(Serialize)]
+struct RunSummary<'a> {
+    subject: &'a str,
+    total: usize,
+    validated: usize,
+    saved: usize,
+    skipped: usize,
+    duration_secs: f64,
+    started_at: String,
+    finished_at: String,
+}
+
+// What the web UI's /status reports about the search it started last
+#[derive(Default, Clone, Serialize)]
+pub struct SearchProgress {
+    pub running: bool,
+    pub processed: usize,
+    // The number of search results, or --max-results while they're still streaming in
+    pub total: usize,
+    pub saved: usize,
+    pub skipped: usize,
+}
+
+// Stops one of run()'s background tasks (the --fsync interval, the Ctrl-C watcher) however
+// run() ends
+struct BackgroundTask(tokio::task::JoinHandle<()>);
+
+impl Drop for BackgroundTask {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+// Outcome of asking the model about one paper
+enum Verdict {
+    Scored { relevant: bool, score: f32, reason: String },
+    // No usable score (refusal, boilerplate, LLM budget spent), the string is saved as the reason
+    NeedsReview(String),
+}
+
+pub struct DOIScraper {
+    client: Client,
+    ollama: Option<Ollama>,
+    // Behind locks so up to --concurrency papers can be processed at once
+    processed_dois: Mutex<KeyStore>,
+    rejected_dois: Mutex<HashSet<String>>,
+    processed_titles: Mutex<TitleIndex>,
+    // Non-DOI identifiers of saved papers as "pmid:123", see identifier_keys
+    processed_ids: Mutex<KeyStore>,
+    // Lowercased engine name (or "default") -> --snippet-trust level
+    snippet_trust: HashMap<String, SnippetTrust>,
+    args: Args,
+    doi_regex: Regex,
+    use_ai: bool,
+    logger: Logger,
+    limiter: Arc<RateLimiter>,
+    searcher: Searcher,
+    notion: Option<NotionExporter>,
+    template: Option<TemplateWriter>,
+    llm_calls: AtomicUsize,
+    llm_unscored: AtomicUsize,
+    // Each saved paper is sent here as JSON, the web UI forwards them over its WebSocket
+    paper_events: Option<broadcast::Sender<String>>,
+    // Counts for the web UI's progress bar
+    progress: Option<Arc<Mutex<SearchProgress>>>,
+    timings: Mutex<PhaseTimes>,
+    // Spaces out page, PDF and metadata requests per host, --rate-limit-ms apart
+    domain_limiter: DomainRateLimiter,
+    // NCBI allows three E-utilities requests a second without an API key
+    ncbi_limiter: RateLimiter,
+    metadata_cache: Option<MetadataCache>,
+    // --db, replaces the --output file when set
+    db: Option<ResultsDb>,
+    // Exact publication date range from --time-range, SearXNG's own filter is much coarser
+    date_bounds: Option<(NaiveDate, NaiveDate)>,
+    // --lang, empty keeps every language
+    languages: Vec<whatlang::Lang>,
+    // Rendered --few-shot-examples, prepended to topic prompts. Empty without any history
+    few_shot: String,
+    // --prompt-template's contents, None for the built-in prompts
+    prompt_template: Option<String>,
+    // --score-mode embedding, each subject only needs embedding once per run
+    subject_embeddings: Mutex<HashMap<String, Vec<f32>>>,
+    // Set on Ctrl-C, no new results are started after it
+    interrupted: Arc<AtomicBool>,
+}
+
+impl DOIScraper {
+    pub async fn new(args: Args) -> Result<Self> {
+        Self::new_with_logger(args, None, None).await
+    }
+
+    fn safe_truncate(s: &str, max_len: usize) -> &str {
+        if s.len() <= max_len {
+            return s;
+        }
+        
+        // Find the last valid char boundary at or before max_len
+        let mut end = max_len;
+        while end > 0 && !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        &s[..end]
+    }
+
+    pub async fn new_with_logger(args: Args, logger: Logger, limiter: Option<Arc<RateLimiter>>) -> Result<Self> {
+        // Only a fallback for requests that don't set their own, searches and page fetches pick
+        // a new one every time
+        let mut builder = Client::builder()
+            .user_agent(useragent::pick(&args.user_agent))
+            .timeout(Duration::from_secs(30))
+            .pool_idle_timeout(Duration::from_secs(args.pool_idle_timeout_secs));
+        if let Some(max_idle) = args.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle);
+        }
+        // reqwest reads the environment variables itself, an explicit --proxy replaces them
+        if !args.proxy.is_empty() {
+            let proxy = reqwest::Proxy::all(&args.proxy)
+                .map_err(|e| anyhow!("invalid --proxy {}: {}", args.proxy, e))?;
+            builder = builder.proxy(proxy);
+            Self::log(&logger, &format!("Using proxy {}", Self::redact_proxy(&args.proxy)));
+        } else if let Some((var, value)) = ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"].iter()
+            .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()).map(|v| (var, v)))
+        {
+            Self::log(&logger, &format!("Using proxy {} from {}", Self::redact_proxy(&value), var));
+        }
+        let client = builder.build()?;
+
+        let (ollama, use_ai) = if args.no_ai {
+            Self::log(&logger, &format!("{}", "=".repeat(64)));
+            Self::log(&logger, "AI validation is disabled (--no-ai flag)");
+            Self::log(&logger, &format!("{}\n", "=".repeat(64)));
+            (None, false)
+        } else {
+            let url = args.ollama_url.trim_end_matches('/');
+            let (host, port) = if let Some(idx) = url.rfind(':') {
+                let port_str = &url[idx+1..];
+                if let Ok(port) = port_str.parse::<u16>() {
+                    (&url[..idx], port)
+                } else {
+                    (url, 11434)
+                }
+            } else {
+                (url, 11434)
+            };
+            
+            let ollama_client = Ollama::new(host, port);
+            match ollama_client.list_local_models().await {
+                Ok(models) => {
+                    // Every prompt to a model that isn't pulled fails, and a failed prompt counts
+                    // as the default accept score, so papers would all pass unjudged
+                    let model = if args.score_mode == ScoreMode::Embedding { &args.embedding_model } else { &args.model };
+                    let names: Vec<&str> = models.iter().map(|m| m.name.as_str()).collect();
+                    if !Self::model_installed(&names, model) {
+                        let available = if names.is_empty() { "none, see `ollama pull`".to_string() } else { names.join(", ") };
+                        return Err(anyhow!("Ollama at {}:{} has no model '{}' (available: {}). Pull it with `ollama pull {}` or run with --no-ai", host, port, model, available, model));
+                    }
+                    Self::log(&logger, &format!("{}", "=".repeat(64)));
+                    Self::log(&logger, &format!("Ollama available at: {}:{}", host, port));
+                    Self::log(&logger, &format!("Model: {}", args.model));
+                    Self::log(&logger, &format!("{}\n", "=".repeat(64)));
+                    (Some(ollama_client), true)
+                }
+                Err(_) => {
+                    Self::log(&logger, &format!("{}", "=".repeat(64)));
+                    Self::warn(&logger, &format!("Ollama not available at: {}:{}", host, port));
+                    Self::warn(&logger, "AI validation disabled");
+                    Self::log(&logger, &format!("{}\n", "=".repeat(64)));
+                    (None, false)
+                }
+            }
+        };
+
+        let limiter = limiter.unwrap_or_else(|| Arc::new(RateLimiter::new(Duration::from_millis(args.api_interval_ms))));
+        let domain_limiter = DomainRateLimiter::new(Duration::from_millis(args.rate_limit_ms));
+        let metadata_cache = MetadataCache::new(&args.cache_dir, args.cache_ttl_days);
+
+        let notion = if !args.notion_token.is_empty() && !args.notion_database_id.is_empty() {
+            let exporter = NotionExporter::connect(client.clone(), &args.notion_token, &args.notion_database_id).await?;
+            Self::log(&logger, &format!("Notion export enabled (database {})", args.notion_database_id));
+            Some(exporter)
+        } else {
+            None
+        };
+
+        let template = if !args.template_file.is_empty() {
+            let writer = TemplateWriter::load(&args.template_file, &args.template_output)?;
+            Self::log(&logger, &format!("Rendering {} into {}", args.template_file, args.template_output));
+            Some(writer)
+        } else {
+            None
+        };
+
+        let db = if args.db.is_empty() {
+            None
+        } else {
+            let db = ResultsDb::open(&args.db).map_err(|e| anyhow!("could not open database {}: {}", args.db, e))?;
+            Self::log(&logger, &format!("Saving to SQLite database {}", args.db));
+            Some(db)
+        };
+
+        let (processed_dois, processed_titles, processed_ids) = match args.dedup_scope {
+            DedupScope::Persistent if !args.dedup_db.is_empty() => {
+                let mut store = DedupDb::open(&args.dedup_db)
+                    .map_err(|e| anyhow!("could not open dedup database {}: {}", args.dedup_db, e))?;
+                let mut titles = TitleIndex::disk(store.titles, args.title_dedup_threshold);
+                // The output is only read to fill a new database, after that it's the source of truth
+                if titles.is_empty() {
+                    let (saved, saved_dois) = match &db {
+                        Some(db) => (db.papers(None)?, db.dois()?),
+                        None => (results::read_results(&args.output), Self::load_processed_dois(&args.output)),
+                    };
+                    for doi in saved_dois {
+                        store.dois.insert(&doi)?;
+                    }
+                    for paper in &saved {
+                        titles.insert(&paper.title)?;
+                        for key in Self::identifier_keys(&paper.identifiers) {
+                            store.ids.insert(&key)?;
+                        }
+                    }
+                }
+                Self::log(&logger, &format!("Dedup database: {}", args.dedup_db));
+                (store.dois, titles, store.ids)
+            }
+            DedupScope::Persistent => {
+                let (saved, saved_dois) = match &db {
+                    Some(db) => (db.papers(None)?, db.dois()?),
+                    None => (results::read_results(&args.output), Self::load_processed_dois(&args.output)),
+                };
+                let mut titles = TitleIndex::new(args.title_dedup_threshold);
+                let mut ids = HashSet::new();
+                for paper in &saved {
+                    titles.insert(&paper.title)?;
+                    ids.extend(Self::identifier_keys(&paper.identifiers));
+                }
+                (KeyStore::memory(saved_dois), titles, KeyStore::memory(ids))
+            }
+            _ => {
+                if !args.dedup_db.is_empty() {
+                    Self::log(&logger, &format!("--dedup-db ignored with the {:?} dedup scope", args.dedup_scope));
+                }
+                (KeyStore::memory(HashSet::new()), TitleIndex::new(args.title_dedup_threshold), KeyStore::memory(HashSet::new()))
+            }
+        };
+
+        if args.forget_rejections && args.benchmark.is_empty() && !args.dry_run && fs::remove_file(&args.rejections_file).is_ok() {
+            Self::log(&logger, &format!("Forgot remembered rejections in {}", args.rejections_file));
+        }
+        let rejected_dois = if args.remember_rejections {
+            Self::load_rejected_dois(&args.rejections_file)
+        } else {
+            HashSet::new()
+        };
+
+        let snippet_trust = Self::parse_snippet_trust(&args.snippet_trust)?;
+
+        if args.score_mode == ScoreMode::Embedding && use_ai {
+            Self::log(&logger, &format!("Scoring by embedding similarity with {}", args.embedding_model));
+            if args.search_mode == SearchMode::Author {
+                Self::warn(&logger, "Embeddings compare topics, they can't tell who wrote a paper, author searches will score poorly");
+            }
+            if args.few_shot_examples > 0 {
+                Self::log(&logger, "--few-shot-examples only applies to --score-mode llm");
+            }
+        }
+
+        let few_shot = if args.few_shot_examples > 0 && use_ai && args.score_mode == ScoreMode::Llm {
+            let examples = feedback::load_examples(&args.decisions_file, &args.subjects(), args.few_shot_examples);
+            if examples.is_empty() {
+                Self::log(&logger, &format!("No decisions in {}, scoring without examples", args.decisions_file));
+            } else {
+                Self::log(&logger, &format!("Few-shot examples from {}", args.decisions_file));
+            }
+            examples
+        } else {
+            String::new()
+        };
+        if args.balance_engines && args.stream_results {
+            Self::warn(&logger, "--balance-engines needs the whole search before picking, it's ignored with --stream-results");
+        }
+        let prompt_template = Self::load_prompt_template(&args.prompt_template)?;
+        if prompt_template.is_some() {
+            Self::log(&logger, &format!("Scoring with the prompt in {}", args.prompt_template));
+        }
+        let date_bounds = dates::time_range_bounds(&args.time_range, chrono::Local::now().date_naive())?;
+        if let (Some(since), Some(until)) = (args.since_year, args.until_year)
+            && since > until
+        {
+            return Err(anyhow!("--since-year {} is after --until-year {}", since, until));
+        }
+        let languages = language::parse_languages(&args.lang)?;
+        if args.open_access_only && args.unpaywall_email.trim().is_empty() {
+            return Err(anyhow!("--open-access-only needs --unpaywall-email, Unpaywall refuses requests without a contact address"));
+        }
+
+        let doi_regex = Regex::new(r"10\.\d{4,9}/[-._;()/:A-Za-z0-9]+").unwrap();
+
+        Self::log(&logger, &format!("{}", "=".repeat(64)));
+        Self::log(&logger, "   SearXNG Scientific DOI Scraper with AI Validation");
+        Self::log(&logger, &format!("{}", "=".repeat(64)));
+        match args.subjects().as_slice() {
+            [subject] => Self::log(&logger, &format!("\nSubject: {}", subject)),
+            subjects => Self::log(&logger, &format!("\nSubjects: {}", subjects.join(" | "))),
+        }
+        if args.search_mode != SearchMode::Topic {
+            Self::log(&logger, &format!("Search mode: {:?}", args.search_mode));
+        }
+        Self::log(&logger, &format!("Instance: {}", args.instance));
+        Self::log(&logger, &format!("Engines: {}", args.engines));
+        
+        if !args.time_range.is_empty() {
+            Self::log(&logger, &format!("Time range: {}", args.time_range));
+        } else {
+            Self::log(&logger, "Time range: all time");
+        }
+        
+        Self::log(&logger, &format!("Max results: {}", args.max_results));
+        Self::log(&logger, &format!("Min score: {:.1}", args.min_score));
+        Self::log(&logger, &format!("Output: {}", args.output));
+        Self::log(&logger, &format!("Dedup scope: {:?}", args.dedup_scope));
+        if args.dedup_scope == DedupScope::Persistent {
+            Self::log(&logger, &format!("Previously processed: {} DOIs", processed_dois.len()));
+        }
+        if args.title_dedup_threshold < 1.0 && args.dedup_scope != DedupScope::None {
+            Self::log(&logger, &format!("Title dedup: {} titles, threshold {:.2}", processed_titles.len(), args.title_dedup_threshold));
+        }
+        if args.remember_rejections {
+            Self::log(&logger, &format!("Previously rejected: {} DOIs", rejected_dois.len()));
+        }
+        Self::log(&logger, "");
+
+        let searcher = Searcher::new(client.clone(), args.clone(), logger.clone());
+
+        Ok(Self {
+            client,
+            ollama,
+            processed_dois: Mutex::new(processed_dois),
+            rejected_dois: Mutex::new(rejected_dois),
+            processed_titles: Mutex::new(processed_titles),
+            processed_ids: Mutex::new(processed_ids),
+            snippet_trust,
+            args,
+            doi_regex,
+            use_ai,
+            logger,
+            limiter,
+            searcher,
+            notion,
+            template,
+            llm_calls: AtomicUsize::new(0),
+            llm_unscored: AtomicUsize::new(0),
+            paper_events: None,
+            progress: None,
+            timings: Mutex::new(PhaseTimes::default()),
+            domain_limiter,
+            ncbi_limiter: RateLimiter::new(Duration::from_millis(334)),
+            metadata_cache,
+            db,
+            date_bounds,
+            languages,
+            few_shot,
+            prompt_template,
+            subject_embeddings: Mutex::new(HashMap::new()),
+            interrupted: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    // Ollama lists "llama3:latest" for a model pulled as plain "llama3"
+    fn model_installed(names: &[&str], model: &str) -> bool {
+        let with_tag = |name: &str| if name.contains(':') { name.to_string() } else { format!("{}:latest", name) };
+        names.iter().any(|name| with_tag(name) == with_tag(model))
+    }
+
+    pub fn set_paper_events(&mut self, events: broadcast::Sender<String>) {
+        self.paper_events = Some(events);
+    }
+
+    pub fn set_progress(&mut self, progress: Arc<Mutex<SearchProgress>>) {
+        self.progress = Some(progress);
+    }
+
+    fn update_progress(&self, update: impl FnOnce(&mut SearchProgress)) {
+        if let Some(progress) = &self.progress {
+            update(&mut progress.lock().unwrap());
+        }
+    }
+
+    fn log(logger: &Logger, message: &str) {
+        logs::write(logger, LogLevel::Info, message);
+    }
+
+    fn debug(logger: &Logger, message: &str) {
+        logs::write(logger, LogLevel::Debug, message);
+    }
+
+    fn warn(logger: &Logger, message: &str) {
+        logs::write(logger, LogLevel::Warn, message);
+    }
+
+    fn error(logger: &Logger, message: &str) {
+        logs::write(logger, LogLevel::Error, message);
+    }
+
+    // Proxy URLs can carry a password, which doesn't belong in the log
+    fn redact_proxy(proxy: &str) -> String {
+        match reqwest::Url::parse(proxy) {
+            Ok(mut url) if url.password().is_some() => {
+                let _ = url.set_password(Some("***"));
+                url.to_string()
+            }
+            _ => proxy.to_string(),
+        }
+    }
+
+    // "NA" marks a paper saved without a DOI, those are caught by the title index instead
+    fn load_processed_dois(filepath: &str) -> HashSet<String> {
+        results::read_results(filepath)
+            .into_iter()
+            .map(|paper| paper.doi)
+            .filter(|doi| !doi.is_empty() && doi != "NA")
+            .collect()
+    }
+
+    // One DOI per line
+    fn load_rejected_dois(filepath: &str) -> HashSet<String> {
+        fs::read_to_string(filepath)
+            .map(|contents| {
+                contents.lines()
+                    .map(|line| line.trim().to_string())
+                    .filter(|line| !line.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn clean_doi(&self, doi: &str) -> String {
+        let mut cleaned = doi.trim().to_string();
+        
+        if cleaned.starts_with("https://doi.org/") {
+            cleaned = cleaned[16..].to_string();
+        } else if cleaned.starts_with("http://doi.org/") {
+            cleaned = cleaned[15..].to_string();
+        }
+        
+        if cleaned.starts_with("doi:") {
+            cleaned = cleaned[4..].to_string();
+        }
+        
+        cleaned.trim().to_string()
+    }
+
+    fn extract_doi_from_text(&self, text: &str) -> Option<String> {
+        if let Some(captures) = self.doi_regex.find(text) {
+            return Some(self.clean_doi(captures.as_str()));
+        }
+        None
+    }
+
+    // Like extract_doi_from_text, but prose wraps DOIs in punctuation the regex swallows:
+    // "(doi: 10.1000/xyz)." should give 10.1000/xyz
+    fn extract_doi_from_snippet(&self, content: &str) -> Option<String> {
+        let found = self.doi_regex.find(content)?;
+        let mut doi = found.as_str().trim_end_matches(['.', ',', ';', ':']);
+        // A closing paren only belongs to the DOI if it closes one inside it, as in 10.1016/S0140-6736(20)
+        while doi.ends_with(')') && doi.matches(')').count() > doi.matches('(').count() {
+            doi = doi[..doi.len() - 1].trim_end_matches(['.', ',', ';', ':']);
+        }
+        Some(self.clean_doi(doi))
+    }
+
+    fn extract_doi_from_url(&self, url: &str) -> Option<String> {
+        if url.contains("doi.org/") {
+            if let Some(doi_part) = url.split("doi.org/").nth(1) {
+                let cleaned = self.clean_doi(doi_part);
+                if self.doi_regex.is_match(&cleaned) {
+                    return Some(cleaned);
+                }
+            }
+        }
+
+        if url.contains("arxiv.org") {
+            if let Some(arxiv_id) = url.split("/abs/").nth(1).or_else(|| url.split("/pdf/").nth(1)) {
+                let id = arxiv_id.split('?').next()
+                    .unwrap_or(arxiv_id)
+                    .trim_end_matches(".pdf");
+                return Some(format!("arXiv:{}", id));
+            }
+        }
+
+        self.extract_doi_from_text(url)
+    }
+
+    // IDs the URL itself carries: PubMed and PMC article pages, arXiv abstracts and PDFs
+    fn identifiers_from_url(url: &str) -> HashMap<String, String> {
+        let mut identifiers = HashMap::new();
+        let path = url.split(['?', '#']).next().unwrap_or(url).trim_end_matches('/');
+        let last = path.rsplit('/').next().unwrap_or("");
+
+        // The current pubmed.ncbi.nlm.nih.gov/123 and the older www.ncbi.nlm.nih.gov/pubmed/123
+        if (path.contains("pubmed.ncbi.nlm.nih.gov/") || path.contains("ncbi.nlm.nih.gov/pubmed/"))
+            && !last.is_empty()
+            && last.chars().all(|c| c.is_ascii_digit())
+        {
+            Self::add_identifier(&mut identifiers, "pmid", last);
+        }
+        if let Some(pmc) = path.split('/').find(|s| s.len() > 3 && s[..3].eq_ignore_ascii_case("pmc") && s[3..].chars().all(|c| c.is_ascii_digit())) {
+            Self::add_identifier(&mut identifiers, "pmcid", pmc);
+        }
+        if path.contains("arxiv.org/") && (path.contains("/abs/") || path.contains("/pdf/")) {
+            Self::add_identifier(&mut identifiers, "arxiv", last.trim_end_matches(".pdf"));
+        }
+        identifiers
+    }
+
+    // The first source to name an ID wins. Values are tidied so the same ID from two sources
+    // compares equal: "PMC123" upper case, no "arXiv:" prefix, DOIs lower case
+    fn add_identifier(identifiers: &mut HashMap<String, String>, kind: &str, id: &str) {
+        let id = id.trim();
+        let id = match kind {
+            "arxiv" => id.trim_start_matches("arXiv:").trim_start_matches("arxiv:").to_string(),
+            "pmcid" => id.to_uppercase(),
+            "doi" => id.to_lowercase(),
+            _ => id.to_string(),
+        };
+        if !id.is_empty() {
+            identifiers.entry(kind.to_string()).or_insert(id);
+        }
+    }
+
+    fn merge_identifiers(identifiers: &mut HashMap<String, String>, other: HashMap<String, String>) {
+        for (kind, id) in other {
+            identifiers.entry(kind).or_insert(id);
+        }
+    }
+
+    // "pmid:123" style keys for processed_ids, DOIs are looked up in processed_dois instead
+    fn identifier_keys(identifiers: &HashMap<String, String>) -> impl Iterator<Item = String> + '_ {
+        identifiers.iter()
+            .filter(|(kind, _)| kind.as_str() != "doi")
+            .map(|(kind, id)| format!("{}:{}", kind, id.to_lowercase()))
+    }
+
+    // DOIs may legally contain (), <>, # and ; which break a URL path when pasted in raw.
+    // Each segment is encoded but the slashes stay, every API expects prefix/suffix
+    fn encode_doi(doi: &str) -> String {
+        doi.trim()
+            .split('/')
+            .map(|segment| urlencoding::encode(segment).into_owned())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    // Served from --cache-dir when a fresh enough copy is there, only successful lookups are
+    // cached so a DOI every API failed on is tried again next run
+    async fn fetch_doi_metadata(&self, doi: &str) -> Result<Metadata> {
+        let Some(cache) = &self.metadata_cache else {
+            return self.fetch_doi_metadata_live(doi).await;
+        };
+        let clean_doi = self.clean_doi(doi);
+        if let Some(metadata) = cache.get::<Metadata>(&clean_doi).await {
+            Self::debug(&self.logger, &format!("      [API] Cached metadata for: {}", clean_doi));
+            return Ok(metadata);
+        }
+
+        let metadata = self.fetch_doi_metadata_live(doi).await?;
+        if let Err(e) = cache.put(&clean_doi, &metadata).await {
+            Self::warn(&self.logger, &format!("      [API] Could not cache metadata for {}: {}", clean_doi, e));
+        }
+        Ok(metadata)
+    }
+
+    async fn fetch_doi_metadata_live(&self, doi: &str) -> Result<Metadata> {
+        let clean_doi = self.clean_doi(doi);
+        // extract_doi_from_url's stand-in for arXiv-only papers, the DOI registries don't know it
+        if let Some(arxiv_id) = clean_doi.strip_prefix("arXiv:") {
+            return self.fetch_arxiv_metadata(arxiv_id).await;
+        }
+        let encoded_doi = Self::encode_doi(&clean_doi);
+
+        // The registries are asked side by side and the first to come back with a title wins,
+        // a slow doi.org no longer holds up a CrossRef that answers straight away. Dropping
+        // select_ok's leftovers cancels the requests still in flight. The race takes one slot
+        // of the shared API limiter, only the per-host limits apply to each request in it
+        self.limiter.wait().await;
+        let registries: Vec<Pin<Box<dyn Future<Output = Result<Metadata>> + Send + '_>>> = vec![
+            Box::pin(self.fetch_doi_org(&clean_doi, &encoded_doi)),
+            Box::pin(self.fetch_crossref(&encoded_doi)),
+            Box::pin(self.fetch_datacite(&encoded_doi)),
+        ];
+        match futures::future::select_ok(registries).await {
+            Ok((metadata, _)) => return Ok(self.with_s2_abstract(&clean_doi, metadata).await),
+            Err(e) => Self::debug(&self.logger, &format!("      [API] DOI registries: {}", e)),
+        }
+
+        match self.fetch_semantic_scholar(&clean_doi).await {
+            Ok(metadata) => return Ok(self.with_core_abstract(&clean_doi, metadata).await),
+            Err(e) => {
+                Self::debug(&self.logger, &format!("      [API] Semantic Scholar: {}", e));
+            }
+        }
+
+        if !self.args.core_api_key.is_empty() {
+            match self.fetch_core(&clean_doi).await {
+                Ok(metadata) => return Ok(metadata),
+                Err(e) => {
+                    Self::debug(&self.logger, &format!("      [API] CORE: {}", e));
+                }
+            }
+        }
+
+        Self::debug(&self.logger, "      [API] Trying OpenAlex");
+
+        let url = format!("https://api.openalex.org/works/doi:{}", encoded_doi);
+        self.limiter.wait().await;
+        self.domain_limiter.wait(&url).await;
+        if let Ok(response) = retry_request(self.client
+            .get(&url)
+            .header("Accept", "application/json")
+            .header("User-Agent", "DOI-APA-Generator/2.0")
+            .timeout(Duration::from_secs(self.args.api_timeout)), self.args.max_retries, &self.logger)
+            .await
+            && response.status().is_success()
+            && let Ok(data) = response.json::<serde_json::Value>().await
+        {
+            let title = data["title"].as_str().or_else(|| data["display_name"].as_str()).unwrap_or("").to_string();
+            let abstract_text = Self::abstract_from_inverted_index(&data["abstract_inverted_index"]);
+            let keywords = data["keywords"].as_array()
+                .map(|arr| arr.iter().filter_map(|k| k["display_name"].as_str()).map(|k| k.to_string()).collect())
+                .unwrap_or_default();
+            let cited_by = data["cited_by_count"].as_u64().map(|n| n as u32);
+            let published = data["publication_date"].as_str().and_then(dates::parse_date);
+            let resource_type = data["type"].as_str().unwrap_or("").to_string();
+            let authors = data["authorships"].as_array()
+                .map(|arr| arr.iter().filter_map(|a| Self::author_name(None, None, a["author"]["display_name"].as_str())).collect())
+                .unwrap_or_default();
+            // Given as URLs: "https://pubmed.ncbi.nlm.nih.gov/123", ".../pmc/articles/PMC456"
+            let mut identifiers = HashMap::new();
+            for kind in ["pmid", "pmcid"] {
+                if let Some(url) = data["ids"][kind].as_str() {
+                    Self::add_identifier(&mut identifiers, kind, url.trim_end_matches('/').rsplit('/').next().unwrap_or(url));
+                }
+            }
+
+            if !title.is_empty() {
+                Self::debug(&self.logger, "      [API] OpenAlex success");
+                return Ok(Metadata { title, abstract_text, keywords, cited_by, published, resource_type, identifiers, authors, ..Default::default() });
+            }
+        }
+
+        Err(anyhow!("All DOI APIs failed"))
+    }
+
+    async fn fetch_doi_org(&self, doi: &str, encoded_doi: &str) -> Result<Metadata> {
+        Self::debug(&self.logger, &format!("      [API] Trying doi.org for: {}", doi));
+        
+        let url = format!("{}/{}", self.args.doi_org_url.trim_end_matches('/'), encoded_doi);
+        self.domain_limiter.wait(&url).await;
+        if let Ok(response) = retry_request(self.client
+            .get(&url)
+            .header("Accept", "application/vnd.citationstyles.csl+json")
+            .header("User-Agent", "DOI-APA-Generator/2.0")
+            .timeout(Duration::from_secs(self.args.api_timeout)), self.args.max_retries, &self.logger)
+            .await
+        {
+            if response.status().is_success() {
+                if let Ok(text) = response.text().await {
+                    if let Ok(data) = serde_json::from_str::<serde_json::Value>(&text) {
+                        if data.get("DOI").is_some() {
+                            let title = data["title"].as_str()
+                                .or_else(|| data["title"].as_array().and_then(|arr| arr[0].as_str()))
+                                .unwrap_or("")
+                                .to_string();
+                            let abstract_text = data["abstract"].as_str().unwrap_or("").to_string();
+                            // CSL has a comma-separated "keyword" string, sometimes a "subject" list instead
+                            let mut keywords = Self::split_keywords(data["keyword"].as_str().unwrap_or(""));
+                            if keywords.is_empty() {
+                                keywords = data["subject"].as_array()
+                                    .map(|arr| arr.iter().filter_map(|s| s.as_str()).map(|s| s.to_string()).collect())
+                                    .unwrap_or_default();
+                            }
+                            let cited_by = data["is-referenced-by-count"].as_u64().map(|n| n as u32);
+                            let published = dates::first_date(&data, &["published", "issued", "published-online", "published-print"]);
+                            let resource_type = data["type"].as_str().unwrap_or("").to_string();
+                            let authors = data["author"].as_array()
+                                .map(|arr| arr.iter()
+                                    .filter_map(|a| Self::author_name(a["given"].as_str(), a["family"].as_str(), a["literal"].as_str().or_else(|| a["name"].as_str())))
+                                    .collect())
+                                .unwrap_or_default();
+                            let mut identifiers = HashMap::new();
+                            for (key, kind) in [("PMID", "pmid"), ("PMCID", "pmcid")] {
+                                if let Some(id) = data[key].as_str().map(|s| s.to_string()).or_else(|| data[key].as_u64().map(|n| n.to_string())) {
+                                    Self::add_identifier(&mut identifiers, kind, &id);
+                                }
+                            }
+                            
+                            if !title.is_empty() {
+                                Self::debug(&self.logger, "      [API] doi.org success");
+                                let metadata = Metadata { title, abstract_text, keywords, cited_by, published, resource_type, identifiers, authors, ..Default::default() };
+                                return Ok(metadata);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Err(anyhow!("doi.org has no title for this DOI"))
+    }
+
+    async fn fetch_crossref(&self, encoded_doi: &str) -> Result<Metadata> {
+        Self::debug(&self.logger, "      [API] Attempting via CrossRef");
+        
+        let url = format!("{}/works/{}", self.args.crossref_url.trim_end_matches('/'), encoded_doi);
+        self.domain_limiter.wait(&url).await;
+        if let Ok(response) = retry_request(self.client
+            .get(&url)
+            .header("Accept", "application/json")
+            .header("User-Agent", "DOI-APA-Generator/2.0")
+            .timeout(Duration::from_secs(self.args.api_timeout)), self.args.max_retries, &self.logger)
+            .await
+        {
+            if response.status().is_success() {
+                if let Ok(data) = response.json::<CrossRefResponse>().await {
+                    let title = data.message.title.first()
+                        .map(|s| s.to_string())
+                        .unwrap_or_default();
+                    // CrossRef abstracts usually come wrapped in JATS XML
+                    let abstract_text = Self::clean_abstract(data.message.abstract_text.as_deref().unwrap_or_default());
+                    let keywords = data.message.subject.clone();
+                    let cited_by = data.message.cited_by;
+                    // "published" is the earlier of print and online when CrossRef has both
+                    let message = &data.message;
+                    let published = [&message.published, &message.issued, &message.published_online, &message.published_print]
+                        .into_iter()
+                        .find_map(dates::from_date_parts);
+                    let resource_type = data.message.kind.clone();
+                    let authors = data.message.author.iter()
+                        .filter_map(|a| Self::author_name(a.given.as_deref(), a.family.as_deref(), a.name.as_deref()))
+                        .collect();
+                    
+                    if !title.is_empty() {
+                        Self::debug(&self.logger, "      [API] CrossRef success");
+                        let metadata = Metadata { title, abstract_text, keywords, cited_by, published, resource_type, authors, ..Default::default() };
+                        return Ok(metadata);
+                    }
+                }
+            }
+        }
+        Err(anyhow!("CrossRef has no title for this DOI"))
+    }
+
+    async fn fetch_datacite(&self, encoded_doi: &str) -> Result<Metadata> {
+        Self::debug(&self.logger, "      [API] Trying DataCite");
+        
+        let url = format!("{}/dois/{}", self.args.datacite_url.trim_end_matches('/'), encoded_doi);
+        self.domain_limiter.wait(&url).await;
+        if let Ok(response) = retry_request(self.client
+            .get(&url)
+            .header("Accept", "application/json")
+            .header("User-Agent", "DOI-APA-Generator/2.0")
+            .timeout(Duration::from_secs(self.args.api_timeout)), self.args.max_retries, &self.logger)
+            .await
+        {
+            if response.status().is_success() {
+                if let Ok(data) = response.json::<DataCiteResponse>().await {
+                    let title = data.data.attributes.titles.first()
+                        .map(|t| t.title.clone())
+                        .unwrap_or_default();
+                    let abstract_text = data.data.attributes.descriptions.first()
+                        .map(|d| d.description.clone())
+                        .unwrap_or_default();
+                    let keywords = data.data.attributes.subjects.iter()
+                        .map(|s| s.subject.clone())
+                        .collect();
+                    let attributes = &data.data.attributes;
+                    let published = attributes.dates.iter()
+                        .filter(|d| d.date_type == "Issued")
+                        .find_map(|d| dates::parse_date(&d.date))
+                        .or_else(|| match &attributes.publication_year {
+                            serde_json::Value::Number(year) => dates::parse_date(&year.to_string()),
+                            serde_json::Value::String(year) => dates::parse_date(year),
+                            _ => None,
+                        });
+                    let resource_type = attributes.types.resource_type_general.clone();
+                    let authors = attributes.creators.iter()
+                        .filter_map(|c| Self::author_name(c.given_name.as_deref(), c.family_name.as_deref(), Some(&c.name)))
+                        .collect();
+                    
+                    if !title.is_empty() {
+                        Self::debug(&self.logger, "      [API] DataCite success");
+                        return Ok(Metadata { title, abstract_text, keywords, published, resource_type, authors, ..Default::default() });
+                    }
+                }
+            }
+        }
+        Err(anyhow!("DataCite has no title for this DOI"))
+    }
+
+    // The free copy's URL, None when Unpaywall knows of none. arXiv papers are free by definition
+    async fn open_access_url(&self, doi: Option<&str>, identifiers: &HashMap<String, String>) -> Result<Option<String>> {
+        if let Some(arxiv) = identifiers.get("arxiv") {
+            return Ok(Some(format!("https://arxiv.org/pdf/{}", arxiv)));
+        }
+        let doi = doi.ok_or_else(|| anyhow!("no DOI to look up"))?;
+
+        self.limiter.wait().await;
+        let response = retry_request(self.client
+            .get(format!("https://api.unpaywall.org/v2/{}", Self::encode_doi(doi)))
+            .query(&[("email", self.args.unpaywall_email.trim())])
+            .header("Accept", "application/json")
+            .timeout(Duration::from_secs(self.args.api_timeout)), self.args.max_retries, &self.logger)
+            .await?;
+        // Unpaywall only covers Crossref DOIs, anything else is unknown to it
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!("Unpaywall returned {}", response.status()));
+        }
+
+        let data: serde_json::Value = response.json().await?;
+        if !data["is_oa"].as_bool().unwrap_or(false) {
+            return Ok(None);
+        }
+        let location = &data["best_oa_location"];
+        let url = location["url_for_pdf"].as_str()
+            .or_else(|| location["url"].as_str())
+            .map(|u| u.to_string())
+            .unwrap_or_else(|| format!("https://doi.org/{}", doi));
+        Ok(Some(url))
+    }
+
+    // "Given Family" from whichever parts a source has. CrossRef and CSL often give only a
+    // family name, and a lone name in "Family, Given" form is turned around
+    fn author_name(given: Option<&str>, family: Option<&str>, name: Option<&str>) -> Option<String> {
+        let part = |p: Option<&str>| p.map(|p| p.split_whitespace().collect::<Vec<_>>().join(" ")).filter(|p| !p.is_empty());
+        match (part(given), part(family), part(name)) {
+            (Some(given), Some(family), _) => Some(format!("{} {}", given, family)),
+            (None, Some(family), _) => Some(family),
+            (_, None, Some(name)) => match name.split_once(", ") {
+                Some((family, given)) if !given.contains(',') => Some(format!("{} {}", given, family)),
+                _ => Some(name),
+            },
+            (Some(given), None, None) => Some(given),
+            (None, None, None) => None,
+        }
+    }
+
+    // The registries often have no abstract for a paper Semantic Scholar or CORE has one for
+    async fn with_s2_abstract(&self, doi: &str, mut metadata: Metadata) -> Metadata {
+        if metadata.abstract_text.is_empty()
+            && let Ok(s2) = self.fetch_semantic_scholar(doi).await
+        {
+            metadata.abstract_text = s2.abstract_text;
+            if metadata.authors.is_empty() {
+                metadata.authors = s2.authors;
+            }
+            Self::merge_identifiers(&mut metadata.identifiers, s2.identifiers);
+        }
+        self.with_core_abstract(doi, metadata).await
+    }
+
+    // Only with --core-api-key, CORE doesn't answer anonymous requests
+    async fn with_core_abstract(&self, doi: &str, mut metadata: Metadata) -> Metadata {
+        if metadata.abstract_text.is_empty()
+            && !self.args.core_api_key.is_empty()
+            && let Ok(core) = self.fetch_core(doi).await
+        {
+            metadata.abstract_text = core.abstract_text;
+            if metadata.authors.is_empty() {
+                metadata.authors = core.authors;
+            }
+            Self::merge_identifiers(&mut metadata.identifiers, core.identifiers);
+        }
+        metadata
+    }
+
+    // CORE's search, narrowed to the DOI. Repository records often lack an abstract but have
+    // the full text, whose opening stands in for one then
+    async fn fetch_core(&self, doi: &str) -> Result<Metadata> {
+        Self::debug(&self.logger, "      [API] Trying CORE");
+
+        let url = "https://api.core.ac.uk/v3/search/works";
+        self.limiter.wait().await;
+        self.domain_limiter.wait(url).await;
+        let response = retry_request(self.client
+            .get(url)
+            .query(&[("q", format!("doi:\"{}\"", doi).as_str()), ("limit", "1")])
+            .bearer_auth(&self.args.core_api_key)
+            .header("Accept", "application/json")
+            .timeout(Duration::from_secs(self.args.api_timeout)), self.args.max_retries, &self.logger)
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("returned {}", response.status()));
+        }
+
+        let data: serde_json::Value = response.json().await?;
+        let work = data["results"].as_array()
+            .and_then(|works| works.iter().find(|w| w["doi"].as_str().is_some_and(|d| d.eq_ignore_ascii_case(doi))))
+            .ok_or_else(|| anyhow!("no record for {}", doi))?;
+
+        let title = work["title"].as_str().unwrap_or("").trim().to_string();
+        if title.is_empty() {
+            return Err(anyhow!("no title for {}", doi));
+        }
+        let abstract_text = match work["abstract"].as_str().map(Self::clean_abstract).filter(|a| !a.is_empty()) {
+            Some(abstract_text) => abstract_text,
+            None => {
+                let full_text = work["fullText"].as_str().unwrap_or("").split_whitespace().collect::<Vec<_>>().join(" ");
+                Self::safe_truncate(&full_text, 1500).to_string()
+            }
+        };
+        let published = work["publishedDate"].as_str()
+            .and_then(dates::parse_date)
+            .or_else(|| work["yearPublished"].as_u64().and_then(|year| dates::parse_date(&year.to_string())));
+        let authors = work["authors"].as_array()
+            .map(|arr| arr.iter().filter_map(|a| Self::author_name(None, None, a["name"].as_str())).collect())
+            .unwrap_or_default();
+        let mut identifiers = HashMap::new();
+        for (key, kind) in [("pubmedId", "pmid"), ("arxivId", "arxiv")] {
+            if let Some(id) = work[key].as_str().filter(|id| !id.is_empty()) {
+                Self::add_identifier(&mut identifiers, kind, id);
+            }
+        }
+
+        Self::debug(&self.logger, "      [API] CORE success");
+        Ok(Metadata { title, abstract_text, published, identifiers, authors, ..Default::default() })
+    }
+
+    async fn fetch_semantic_scholar(&self, doi: &str) -> Result<Metadata> {
+        Self::debug(&self.logger, "      [API] Trying Semantic Scholar");
+
+        self.limiter.wait().await;
+        let mut request = self.client
+            .get(format!("https://api.semanticscholar.org/graph/v1/paper/DOI:{}", Self::encode_doi(doi)))
+            .query(&[("fields", "title,abstract,year,authors,publicationDate,externalIds")])
+            .header("Accept", "application/json")
+            .timeout(Duration::from_secs(self.args.api_timeout));
+        if !self.args.s2_api_key.is_empty() {
+            request = request.header("x-api-key", &self.args.s2_api_key);
+        }
+        // Not retried: without a key 429s are routine and the other sources are just as good
+        let response = request.send().await?;
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(anyhow!("rate limited{}", if self.args.s2_api_key.is_empty() { ", --s2-api-key raises the limit" } else { "" }));
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!("returned {}", response.status()));
+        }
+
+        let data: serde_json::Value = response.json().await?;
+        let title = data["title"].as_str().unwrap_or("").trim().to_string();
+        if title.is_empty() {
+            return Err(anyhow!("no title for {}", doi));
+        }
+        let abstract_text = data["abstract"].as_str().map(Self::clean_abstract).unwrap_or_default();
+        let published = data["publicationDate"].as_str()
+            .and_then(dates::parse_date)
+            .or_else(|| data["year"].as_u64().and_then(|year| dates::parse_date(&year.to_string())));
+        let authors = data["authors"].as_array()
+            .map(|arr| arr.iter().filter_map(|a| Self::author_name(None, None, a["name"].as_str())).collect())
+            .unwrap_or_default();
+        let mut identifiers = HashMap::new();
+        for (key, kind) in [("PubMed", "pmid"), ("PubMedCentral", "pmcid"), ("ArXiv", "arxiv")] {
+            if let Some(id) = data["externalIds"][key].as_str() {
+                // Semantic Scholar drops the prefix from PMC IDs
+                let id = if kind == "pmcid" && !id.to_uppercase().starts_with("PMC") { format!("PMC{}", id) } else { id.to_string() };
+                Self::add_identifier(&mut identifiers, kind, &id);
+            }
+        }
+
+        Self::debug(&self.logger, "      [API] Semantic Scholar success");
+        Ok(Metadata { title, abstract_text, published, identifiers, authors, ..Default::default() })
+    }
+
+    // arXiv's export API answers with an Atom feed holding one <entry>. html5ever parses it
+    // well enough, namespaced tags like <arxiv:doi> just keep the prefix in their name
+    async fn fetch_arxiv_metadata(&self, arxiv_id: &str) -> Result<Metadata> {
+        Self::debug(&self.logger, "      [API] Trying arXiv");
+
+        let url = format!("https://export.arxiv.org/api/query?id_list={}", urlencoding::encode(arxiv_id));
+        self.limiter.wait().await;
+        self.domain_limiter.wait(&url).await;
+        let response = retry_request(self.client
+            .get(&url)
+            .header("Accept", "application/atom+xml")
+            .timeout(Duration::from_secs(self.args.api_timeout)), self.args.max_retries, &self.logger)
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("arXiv returned {}", response.status()));
+        }
+
+        let feed = Html::parse_document(&response.text().await?);
+        let text_of = |selector: &str| {
+            Selector::parse(selector).ok()
+                .and_then(|s| feed.select(&s).next().map(|e| e.text().collect::<String>()))
+                .map(|t| t.split_whitespace().collect::<Vec<_>>().join(" "))
+                .unwrap_or_default()
+        };
+
+        // An unknown or malformed ID still gets an entry, titled "Error"
+        if text_of("entry > id").contains("/api/errors") {
+            return Err(anyhow!("arXiv has no paper {}: {}", arxiv_id, text_of("entry > summary")));
+        }
+        let title = text_of("entry > title");
+        if title.is_empty() {
+            return Err(anyhow!("arXiv has no paper {}", arxiv_id));
+        }
+        let abstract_text = Self::clean_abstract(&text_of("entry > summary"));
+        let published = dates::parse_date(text_of("entry > published").get(..10).unwrap_or(""));
+        let authors = Selector::parse("entry > author > name").ok()
+            .map(|s| feed.select(&s)
+                .filter_map(|e| Self::author_name(None, None, Some(&e.text().collect::<String>())))
+                .collect())
+            .unwrap_or_default();
+        let mut identifiers = HashMap::new();
+        Self::add_identifier(&mut identifiers, "arxiv", arxiv_id);
+        // The journal version's DOI, when the authors have told arXiv about one
+        let journal_doi = text_of("entry > arxiv\\:doi");
+        if !journal_doi.is_empty() {
+            Self::add_identifier(&mut identifiers, "doi", &journal_doi);
+        }
+
+        Self::debug(&self.logger, "      [API] arXiv success");
+        Ok(Metadata { title, abstract_text, published, identifiers, authors, resource_type: "preprint".to_string(), ..Default::default() })
+    }
+
+    // efetch only takes PMIDs, a PMC article is mapped to its PMID through esummary first
+    async fn fetch_pubmed_metadata(&self, identifiers: &HashMap<String, String>) -> Result<Metadata> {
+        let pmid = match (identifiers.get("pmid"), identifiers.get("pmcid")) {
+            (Some(pmid), _) => pmid.clone(),
+            (None, Some(pmcid)) => self.pmid_for_pmcid(pmcid).await?,
+            (None, None) => return Err(anyhow!("no PMID or PMCID")),
+        };
+        Self::debug(&self.logger, &format!("      [API] Trying PubMed for PMID {}", pmid));
+
+        self.limiter.wait().await;
+        self.ncbi_limiter.wait().await;
+        let response = retry_request(self.client
+            .get("https://eutils.ncbi.nlm.nih.gov/entrez/eutils/efetch.fcgi")
+            .query(&[("db", "pubmed"), ("id", pmid.as_str()), ("retmode", "xml")])
+            .header("Accept", "application/xml")
+            .timeout(Duration::from_secs(self.args.api_timeout)), self.args.max_retries, &self.logger)
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("PubMed returned {}", response.status()));
+        }
+
+        // The HTML parser lower-cases the XML's element and attribute names
+        let record = Html::parse_document(&response.text().await?);
+        let select = |selector: &str| {
+            Selector::parse(selector).ok()
+                .map(|s| record.select(&s)
+                    .map(|e| e.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" "))
+                    .filter(|t| !t.is_empty())
+                    .collect::<Vec<_>>())
+                .unwrap_or_default()
+        };
+        let text_of = |selector: &str| select(selector).into_iter().next().unwrap_or_default();
+
+        let title = text_of("medlinecitation > article > articletitle").trim_end_matches('.').to_string();
+        if title.is_empty() {
+            return Err(anyhow!("PubMed has no article {}", pmid));
+        }
+
+        // Structured abstracts come as one AbstractText per section, labelled BACKGROUND, METHODS, ...
+        let sections = Selector::parse("article > abstract > abstracttext").ok()
+            .map(|s| record.select(&s)
+                .map(|e| {
+                    let text = e.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ");
+                    match e.value().attr("label") {
+                        Some(label) if !text.is_empty() => format!("{}: {}", label, text),
+                        _ => text,
+                    }
+                })
+                .filter(|t| !t.is_empty())
+                .collect::<Vec<_>>())
+            .unwrap_or_default();
+        let abstract_text = Self::clean_abstract(&sections.join(" "));
+
+        let authors = Selector::parse("authorlist > author").ok()
+            .map(|s| record.select(&s)
+                .filter_map(|author| {
+                    let part = |name: &str| Selector::parse(name).ok()
+                        .and_then(|s| author.select(&s).next().map(|e| e.text().collect::<String>()));
+                    Self::author_name(part("forename").as_deref(), part("lastname").as_deref(), part("collectivename").as_deref())
+                })
+                .collect())
+            .unwrap_or_default();
+
+        // The electronic date has numeric parts, the issue's PubDate spells months out ("May")
+        // or is a free-form MedlineDate ("1998 Dec-1999 Jan")
+        const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+        let date_of = |prefix: &str| {
+            let year = text_of(&format!("{} > year", prefix));
+            let month = text_of(&format!("{} > month", prefix)).to_lowercase();
+            let month = month.parse::<u32>().ok()
+                .or_else(|| MONTHS.iter().position(|m| month.starts_with(m)).map(|i| i as u32 + 1));
+            let day = text_of(&format!("{} > day", prefix)).parse::<u32>().ok();
+            match (month, day) {
+                (Some(month), Some(day)) => dates::parse_date(&format!("{}-{:02}-{:02}", year, month, day)),
+                (Some(month), None) => dates::parse_date(&format!("{}-{:02}", year, month)),
+                _ => dates::parse_date(&year),
+            }
+        };
+        let published = date_of("article > articledate")
+            .or_else(|| date_of("journalissue > pubdate"))
+            .or_else(|| dates::parse_date(&text_of("journalissue > pubdate > medlinedate")));
+
+        let keywords = select("keywordlist > keyword");
+        let mut identifiers = HashMap::new();
+        Self::add_identifier(&mut identifiers, "pmid", &pmid);
+        let pmcid = text_of("pubmeddata > articleidlist > articleid[idtype='pmc']");
+        if !pmcid.is_empty() {
+            Self::add_identifier(&mut identifiers, "pmcid", &pmcid);
+        }
+        let doi = Some(text_of("pubmeddata > articleidlist > articleid[idtype='doi']"))
+            .map(|doi| self.clean_doi(&doi))
+            .filter(|doi| self.doi_regex.is_match(doi));
+
+        Self::debug(&self.logger, "      [API] PubMed success");
+        Ok(Metadata { title, abstract_text, doi, keywords, published, identifiers, authors, ..Default::default() })
+    }
+
+    async fn pmid_for_pmcid(&self, pmcid: &str) -> Result<String> {
+        let id = pmcid.trim_start_matches("PMC");
+        self.limiter.wait().await;
+        self.ncbi_limiter.wait().await;
+        let response = retry_request(self.client
+            .get("https://eutils.ncbi.nlm.nih.gov/entrez/eutils/esummary.fcgi")
+            .query(&[("db", "pmc"), ("id", id), ("retmode", "json")])
+            .header("Accept", "application/json")
+            .timeout(Duration::from_secs(self.args.api_timeout)), self.args.max_retries, &self.logger)
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("PMC returned {}", response.status()));
+        }
+
+        let data: serde_json::Value = response.json().await?;
+        data["result"][id]["articleids"].as_array()
+            .and_then(|ids| ids.iter().find(|a| a["idtype"] == "pmid"))
+            .and_then(|a| a["value"].as_str())
+            .filter(|pmid| !pmid.is_empty() && *pmid != "0")
+            .map(|pmid| pmid.to_string())
+            .ok_or_else(|| anyhow!("no PMID for {}", pmcid))
+    }
+
+    // OpenAlex can't redistribute abstracts verbatim, it sends {"word": [positions...]} instead.
+    // Every word goes back at each of its positions, gaps (positions nobody claims) are skipped
+    fn abstract_from_inverted_index(index: &serde_json::Value) -> String {
+        let Some(index) = index.as_object() else {
+            return String::new();
+        };
+
+        let mut words: Vec<(u64, &str)> = index.iter()
+            .flat_map(|(word, positions)| {
+                positions.as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|p| p.as_u64())
+                    .map(move |p| (p, word.as_str()))
+            })
+            .collect();
+        words.sort_unstable();
+
+        words.iter()
+            .map(|(_, word)| *word)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    // explore.openaire.eu/search/publication?articleId=doi_dedup___::1b2c... and the dataset,
+    // software and other-research-product variants. Record pages are rendered client side so
+    // there's nothing in the HTML to scrape
+    fn openaire_id(url: &str) -> Option<String> {
+        let parsed = reqwest::Url::parse(url).ok()?;
+        if !parsed.host_str()?.contains("openaire") {
+            return None;
+        }
+        parsed.query_pairs()
+            .find(|(key, value)| ["articleId", "datasetId", "softwareId", "orpId", "id"].contains(&key.as_ref()) && value.contains("::"))
+            .map(|(_, value)| value.into_owned())
+    }
+
+    async fn fetch_openaire_metadata(&self, record_id: &str) -> Result<Metadata> {
+        Self::debug(&self.logger, &format!("      [API] Trying OpenAIRE for: {}", record_id));
+
+        self.limiter.wait().await;
+        let response = retry_request(self.client
+            .get(format!("https://api.openaire.eu/graph/v1/researchProducts/{}", urlencoding::encode(record_id)))
+            .header("Accept", "application/json")
+            .timeout(Duration::from_secs(self.args.api_timeout)), self.args.max_retries, &self.logger)
+            .await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("OpenAIRE returned {}", response.status()));
+        }
+
+        let data: serde_json::Value = response.json().await?;
+        let title = data["mainTitle"].as_str().unwrap_or("").trim().to_string();
+        // Several descriptions means several languages or versions, the longest is the likeliest abstract
+        let abstract_text = data["descriptions"].as_array()
+            .and_then(|arr| arr.iter().filter_map(|d| d.as_str()).max_by_key(|d| d.len()))
+            .map(Self::clean_abstract)
+            .unwrap_or_default();
+        let doi = data["pids"].as_array()
+            .and_then(|arr| arr.iter().find(|p| p["scheme"].as_str().is_some_and(|s| s.eq_ignore_ascii_case("doi"))))
+            .and_then(|p| p["value"].as_str())
+            .map(|doi| self.clean_doi(doi));
+        let mut identifiers = HashMap::new();
+        for pid in data["pids"].as_array().into_iter().flatten() {
+            let kind = match pid["scheme"].as_str().unwrap_or("").to_lowercase().as_str() {
+                "pmid" => "pmid",
+                "pmc" | "pmcid" => "pmcid",
+                "arxiv" => "arxiv",
+                _ => continue,
+            };
+            if let Some(id) = pid["value"].as_str() {
+                Self::add_identifier(&mut identifiers, kind, id);
+            }
+        }
+        let keywords = data["subjects"].as_array()
+            .map(|arr| arr.iter().filter_map(|s| s["subject"]["value"].as_str()).map(|s| s.to_string()).collect())
+            .unwrap_or_default();
+        let published = data["publicationDate"].as_str().and_then(dates::parse_date);
+        // "publication", "dataset", "software" or "other"
+        let resource_type = data["type"].as_str().unwrap_or("").to_string();
+        let authors = data["authors"].as_array()
+            .map(|arr| arr.iter().filter_map(|a| Self::author_name(a["name"].as_str(), a["surname"].as_str(), a["fullName"].as_str())).collect())
+            .unwrap_or_default();
+
+        if title.is_empty() {
+            return Err(anyhow!("OpenAIRE record {} has no title", record_id));
+        }
+        Self::debug(&self.logger, "      [API] OpenAIRE success");
+        Ok(Metadata { title, abstract_text, doi, keywords, published, resource_type, identifiers, authors, ..Default::default() })
+    }
+
+    async fn fetch_page_content(&self, url: &str) -> Result<Metadata> {
+        self.domain_limiter.wait(url).await;
+        let response = self.client
+            .get(url)
+            .header("User-Agent", useragent::pick(&self.args.user_agent))
+            .timeout(Duration::from_secs(self.args.page_timeout))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Ok(Metadata::default());
+        }
+
+        let html = response.text().await?;
+        let document = Html::parse_document(&html);
+
+        let meta_selectors = vec![
+            "meta[name='citation_doi']",
+            "meta[name='DC.Identifier']",
+            "meta[property='citation_doi']",
+            "meta[name='DOI']",
+        ];
+
+        let mut doi = None;
+        let mut doi_selector = None;
+        for selector_str in meta_selectors {
+            if let Ok(selector) = Selector::parse(selector_str) {
+                for element in document.select(&selector) {
+                    if let Some(content) = element.value().attr("content") {
+                        if let Some(extracted) = self.extract_doi_from_text(content) {
+                            doi = Some(extracted);
+                            doi_selector = Some(selector_str);
+                            break;
+                        }
+                    }
+                }
+                if doi.is_some() {
+                    break;
+                }
+            }
+        }
+
+        // Body text is a last resort, it's full of reference and "related article" DOIs
+        if doi.is_none() {
+            doi = self.pick_body_doi(&document, url);
+            if doi.is_some() {
+                doi_selector = Some("body text");
+            }
+        }
+
+        let abstract_meta_selectors = vec![
+            "meta[name='citation_abstract']",
+            "meta[name='description']",
+            "meta[property='og:description']",
+            "meta[name='DC.Description']",
+        ];
+
+        let mut abstract_text = String::new();
+        let mut abstract_selector = None;
+        for selector_str in abstract_meta_selectors {
+            if let Ok(selector) = Selector::parse(selector_str) {
+                if let Some(element) = document.select(&selector).next() {
+                    if let Some(content) = element.value().attr("content") {
+                        if content.chars().count() > 50 {
+                            abstract_text = content.to_string();
+                            abstract_selector = Some(selector_str);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        if abstract_text.is_empty() {
+            let content_selectors = vec![
+                "abstract", ".abstract", "#abstract", "div.abstract",
+                "section.abstract", "div[class*='abstract']", "p[class*='abstract']",
+            ];
+
+            for selector_str in content_selectors {
+                if let Ok(selector) = Selector::parse(selector_str) {
+                    if let Some(element) = document.select(&selector).next() {
+                        let text = element.text().collect::<Vec<_>>().join(" ");
+                        if text.chars().count() > 50 {
+                            abstract_text = text.trim().to_string();
+                            abstract_selector = Some(selector_str);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        let keyword_selectors = vec![
+            "meta[name='citation_keywords']",
+            "meta[name='keywords']",
+            "meta[name='DC.Subject']",
+        ];
+
+        // Some publishers use one tag per keyword, others one tag with a list
+        let mut keywords = Vec::new();
+        for selector_str in keyword_selectors {
+            if let Ok(selector) = Selector::parse(selector_str) {
+                for element in document.select(&selector) {
+                    if let Some(content) = element.value().attr("content") {
+                        keywords.extend(Self::split_keywords(content));
+                    }
+                }
+                if !keywords.is_empty() {
+                    break;
+                }
+            }
+        }
+
+        let date_selectors = vec![
+            "meta[name='citation_publication_date']",
+            "meta[name='citation_date']",
+            "meta[name='citation_online_date']",
+            "meta[name='prism.publicationDate']",
+            "meta[name='DC.Date']",
+            "meta[name='dc.date']",
+            "meta[property='article:published_time']",
+        ];
+
+        let mut published = None;
+        for selector_str in date_selectors {
+            if let Ok(selector) = Selector::parse(selector_str) {
+                published = document.select(&selector)
+                    .filter_map(|element| element.value().attr("content"))
+                    .find_map(dates::parse_date);
+                if published.is_some() {
+                    break;
+                }
+            }
+        }
+
+        // Publisher vocabulary ("Research Article", "Editorial", "Review"), rarely more than one tag
+        let type_selectors = vec![
+            "meta[name='citation_article_type']",
+            "meta[name='prism.section']",
+            "meta[name='DC.Type']",
+            "meta[name='dc.type']",
+        ];
+
+        let mut resource_type = String::new();
+        for selector_str in type_selectors {
+            if let Ok(selector) = Selector::parse(selector_str)
+                && let Some(content) = document.select(&selector).find_map(|element| element.value().attr("content"))
+                && !content.trim().is_empty()
+            {
+                resource_type = content.trim().to_string();
+                break;
+            }
+        }
+
+        // One tag per author, "Family, Given" or "Given Family" depending on the publisher
+        let authors = ["meta[name='citation_author']", "meta[name='DC.Creator']", "meta[name='dc.creator']"]
+            .iter()
+            .filter_map(|selector_str| Selector::parse(selector_str).ok())
+            .map(|selector| document.select(&selector)
+                .filter_map(|element| element.value().attr("content"))
+                .filter_map(|name| Self::author_name(None, None, Some(name)))
+                .collect::<Vec<_>>())
+            .find(|authors| !authors.is_empty())
+            .unwrap_or_default();
+
+        let mut identifiers = HashMap::new();
+        for (selector_str, kind) in [
+            ("meta[name='citation_pmid']", "pmid"),
+            ("meta[name='citation_pmcid']", "pmcid"),
+            ("meta[name='citation_arxiv_id']", "arxiv"),
+        ] {
+            if let Ok(selector) = Selector::parse(selector_str)
+                && let Some(content) = document.select(&selector).find_map(|element| element.value().attr("content"))
+            {
+                Self::add_identifier(&mut identifiers, kind, content);
+            }
+        }
+
+        Self::debug(&self.logger, &format!("   [FETCH] DOI selector: {}", doi_selector.unwrap_or("none matched")));
+        Self::debug(&self.logger, &format!("   [FETCH] Abstract selector: {}", abstract_selector.unwrap_or("none matched")));
+
+        Ok(Metadata { abstract_text, doi, keywords, published, resource_type, identifiers, authors, ..Default::default() })
+    }
+
+    // Prefers the DOI the URL itself points at, then whichever DOI the page repeats most. Past
+    // the URL check only DOIs labelled as one count ("DOI: 10...", "https://doi.org/10..."),
+    // a bare 10.xxxx/yyy in running text is as likely a grant or catalogue number
+    fn pick_body_doi(&self, document: &Html, url: &str) -> Option<String> {
+        let body_text = document.root_element().text().collect::<Vec<_>>().join(" ");
+
+        // (DOI, times it appears labelled)
+        let mut candidates: Vec<(String, usize)> = Vec::new();
+        for found in self.doi_regex.find_iter(&body_text) {
+            // Sentence punctuation right after a DOI gets swallowed by the regex
+            let candidate = self.clean_doi(found.as_str().trim_end_matches(['.', ',', ';', ':']));
+            let labelled = usize::from(Self::labelled_as_doi(&body_text, found.start()));
+            match candidates.iter_mut().find(|(c, _)| c.eq_ignore_ascii_case(&candidate)) {
+                Some((_, count)) => *count += labelled,
+                None => candidates.push((candidate, labelled)),
+            }
+        }
+
+        if let Some(url_doi) = self.extract_doi_from_text(url)
+            && let Some((c, _)) = candidates.iter().find(|(c, _)| c.eq_ignore_ascii_case(&url_doi))
+        {
+            return Some(c.clone());
+        }
+
+        // max_by_key keeps the last of equal maxima, so reverse to let the earliest win ties
+        candidates.into_iter()
+            .filter(|(_, count)| *count > 0)
+            .rev()
+            .max_by_key(|(_, count)| *count)
+            .map(|(c, _)| c)
+    }
+
+    // "doi" somewhere in the few characters before the match, which covers "DOI:", "doi.org/"
+    // and "dx.doi.org/"
+    fn labelled_as_doi(text: &str, start: usize) -> bool {
+        const WINDOW: usize = 24;
+        let before = &text[..start];
+        let from = before.char_indices().rev().nth(WINDOW - 1).map_or(0, |(i, _)| i);
+        before[from..].to_lowercase().contains("doi")
+    }
+
+    // Turns JATS/HTML markup into plain text, keeping paragraphs apart with a blank line
+    fn clean_abstract(raw: &str) -> String {
+        if !raw.contains('<') && !raw.contains('&') {
+            return raw.trim().to_string();
+        }
+
+        // Line breaks in the markup mean nothing, only the paragraph tags do
+        let text = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+        // "<jats:title>Abstract</jats:title>" would otherwise become the first word
+        let text = HEADING_TAG.replace_all(&text, " ");
+        let text = PARAGRAPH_TAG.replace_all(&text, "\n");
+        let text = ANY_TAG.replace_all(&text, "");
+        let text = text
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&#39;", "'")
+            .replace("&nbsp;", " ")
+            .replace("&amp;", "&");
+
+        text.lines()
+            .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    // ".../deep-learning-for_protein_folding.html" -> "deep learning for protein folding".
+    // Bare IDs ("/article/12345") make no better title than none at all
+    fn title_from_url(url: &str) -> Option<String> {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        let segment = path.trim_end_matches('/').rsplit('/').next()?;
+        let segment = urlencoding::decode(segment).map(|s| s.into_owned()).unwrap_or_else(|_| segment.to_string());
+        let stem = segment.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(&segment);
+
+        let words: Vec<&str> = stem.split(['-', '_', '+', ' ']).filter(|w| !w.is_empty()).collect();
+        if words.len() < 2 || !words.iter().any(|w| w.chars().any(|c| c.is_alphabetic())) {
+            return None;
+        }
+        Some(words.join(" "))
+    }
+
+    // Ends in an ellipsis, or stops mid-sentence right around a round length like 200 or 250,
+    // which is what fixed-width snippet cutters leave behind
+    fn looks_truncated(text: &str) -> bool {
+        let text = text.trim();
+        if text.ends_with("...") || text.ends_with('…') {
+            return true;
+        }
+
+        let len = text.chars().count();
+        let ends_sentence = text.ends_with(['.', '!', '?', '"', ')']);
+        let near_round = len >= 100 && matches!(len % 50, 0..=2 | 48..=49);
+        !ends_sentence && near_round
+    }
+
+    // One more source for an abstract that came back cut off, whichever of the APIs or the page
+    // hasn't been asked yet
+    async fn complete_abstract(&self, url: &str, doi: Option<&str>, page_fetched: bool, api_fetched: bool) -> Option<String> {
+        if let Some(doi) = doi
+            && !api_fetched
+            && let Ok(api) = self.fetch_doi_metadata(doi).await
+            && !api.abstract_text.is_empty()
+        {
+            return Some(Self::clean_abstract(&api.abstract_text));
+        }
+
+        if !page_fetched
+            && let Ok(page) = self.fetch_page_content(url).await
+            && !page.abstract_text.is_empty()
+        {
+            return Some(Self::clean_abstract(&page.abstract_text));
+        }
+
+        None
+    }
+
+    fn split_keywords(raw: &str) -> Vec<String> {
+        let mut keywords: Vec<String> = Vec::new();
+        for keyword in raw.split([';', ',']).map(|k| k.trim()).filter(|k| !k.is_empty()) {
+            if !keywords.iter().any(|k| k.eq_ignore_ascii_case(keyword)) {
+                keywords.push(keyword.to_string());
+            }
+        }
+        keywords
+    }
+
+    fn parse_snippet_trust(spec: &str) -> Result<HashMap<String, SnippetTrust>> {
+        let mut trust = HashMap::new();
+        for entry in spec.split(',').map(|e| e.trim()).filter(|e| !e.is_empty()) {
+            let (engine, level) = entry.split_once('=')
+                .ok_or_else(|| anyhow!("--snippet-trust entry '{}' should look like engine=level", entry))?;
+            let level = match level.trim().to_lowercase().as_str() {
+                "low" => SnippetTrust::Low,
+                "normal" => SnippetTrust::Normal,
+                "high" => SnippetTrust::High,
+                other => return Err(anyhow!("unknown --snippet-trust level '{}' (use low, normal or high)", other)),
+            };
+            trust.insert(engine.trim().to_lowercase(), level);
+        }
+        Ok(trust)
+    }
+
+    fn snippet_trust_for(&self, engine: &str) -> SnippetTrust {
+        self.snippet_trust.get(&engine.to_lowercase())
+            .or_else(|| self.snippet_trust.get("default"))
+            .copied()
+            .unwrap_or(SnippetTrust::Normal)
+    }
+
+    fn matches_required_keywords(&self, title: &str, abstract_text: &str, keywords: &[String]) -> bool {
+        let required: Vec<String> = self.args.require_keywords.split(',')
+            .map(|k| k.trim().to_lowercase())
+            .filter(|k| !k.is_empty())
+            .collect();
+
+        if required.is_empty() {
+            return true;
+        }
+
+        // Papers without keyword metadata fall back to the text so they aren't dropped blindly
+        if self.args.keyword_source == KeywordSource::Metadata && !keywords.is_empty() {
+            let keywords: Vec<String> = keywords.iter().map(|k| k.to_lowercase()).collect();
+            return required.iter().any(|r| keywords.iter().any(|k| k.contains(r.as_str())));
+        }
+
+        let text = format!("{} {}", title, abstract_text).to_lowercase();
+        required.iter().any(|r| text.contains(r.as_str()))
+    }
+
+    // CrossRef/CSL say "dataset", DataCite says "Dataset"
+    // "Research Article", "research_article" and "research-article" are the same type
+    fn normalize_type(resource_type: &str) -> String {
+        resource_type.trim().to_lowercase().replace([' ', '_'], "-")
+    }
+
+    fn type_list(spec: &str) -> Vec<String> {
+        spec.split(',')
+            .map(Self::normalize_type)
+            .filter(|t| !t.is_empty())
+            .collect()
+    }
+
+    // The --exclude-domains entry the URL's host falls under
+    fn excluded_domain(&self, url: &str) -> Option<&str> {
+        let host = reqwest::Url::parse(url).ok()?.host_str()?.to_lowercase();
+        self.args.exclude_domains.split(',')
+            .map(|d| d.trim().trim_start_matches("*.").trim_start_matches('.'))
+            .filter(|d| !d.is_empty())
+            .find(|d| {
+                let d = d.to_lowercase();
+                host == d || host.ends_with(&format!(".{}", d))
+            })
+    }
+
+    fn type_filter_active(&self) -> bool {
+        !self.args.include_types.trim().is_empty() || !self.args.exclude_types.trim().is_empty()
+    }
+
+    // Why --include-types/--exclude-types/--strict-type rule the paper out, if they do
+    fn type_rejection(&self, resource_type: &str) -> Option<String> {
+        if !self.type_filter_active() {
+            return None;
+        }
+
+        let normalized = Self::normalize_type(resource_type);
+        if normalized.is_empty() {
+            return self.args.strict_type.then(|| "Unknown article type (--strict-type)".to_string());
+        }
+
+        if Self::type_list(&self.args.exclude_types).contains(&normalized) {
+            return Some(format!("Article type '{}' is excluded", resource_type));
+        }
+        let include = Self::type_list(&self.args.include_types);
+        if !include.is_empty() && !include.contains(&normalized) {
+            return Some(format!("Article type '{}' is not in --include-types", resource_type));
+        }
+        None
+    }
+
+    fn is_dataset_type(resource_type: &str) -> bool {
+        resource_type.eq_ignore_ascii_case("dataset")
+    }
+
+    // "2015 to 2020", "2015 onwards", "up to 2020"
+    fn year_range(&self) -> String {
+        match (self.args.since_year, self.args.until_year) {
+            (Some(since), Some(until)) => format!("{} to {}", since, until),
+            (Some(since), None) => format!("{} onwards", since),
+            (None, Some(until)) => format!("up to {}", until),
+            (None, None) => "any year".to_string(),
+        }
+    }
+
+    // Refused when {subject} or {title} is missing, a prompt without them can't judge anything
+    fn load_prompt_template(path: &str) -> Result<Option<String>> {
+        if path.trim().is_empty() {
+            return Ok(None);
+        }
+        let template = fs::read_to_string(path.trim())
+            .map_err(|e| anyhow!("Could not read --prompt-template {}: {}", path, e))?;
+        let missing: Vec<&str> = ["{subject}", "{title}"].into_iter()
+            .filter(|placeholder| !template.contains(placeholder))
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow!("--prompt-template {} has no {} placeholder", path, missing.join(" or ")));
+        }
+        Ok(Some(template))
+    }
+
+    // In one pass, so a title that happens to contain "{abstract}" stays as it is
+    fn fill_prompt(template: &str, subject: &str, title: &str, abstract_text: &str) -> String {
+        let mut prompt = String::with_capacity(template.len() + abstract_text.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            prompt.push_str(&rest[..start]);
+            let tail = &rest[start..];
+            let value = [("{subject}", subject), ("{title}", title), ("{abstract}", abstract_text)]
+                .into_iter()
+                .find(|(placeholder, _)| tail.starts_with(placeholder));
+            match value {
+                Some((placeholder, value)) => {
+                    prompt.push_str(value);
+                    rest = &tail[placeholder.len()..];
+                }
+                None => {
+                    prompt.push('{');
+                    rest = &tail[1..];
+                }
+            }
+        }
+        prompt.push_str(rest);
+        prompt
+    }
+
+    async fn validate_with_ai(&self, title: &str, abstract_text: &str, subject: &str, is_dataset: bool, min_score: f32) -> Result<Verdict> {
+        let ollama = match &self.ollama {
+            Some(o) => o,
+            None => return Ok(Verdict::Scored { relevant: true, score: 1.1, reason: "AI disabled -_-".to_string() }),
+        };
+
+        if self.args.score_mode == ScoreMode::Embedding {
+            return Ok(self.score_by_embedding(ollama, title, abstract_text, subject, min_score).await);
+        }
+
+        if !self.take_llm_call() {
+            if self.llm_unscored.fetch_add(1, Ordering::SeqCst) == 0 {
+                Self::log(&self.logger, &format!("   [AI] LLM call budget of {} exhausted, remaining papers go unscored", self.args.max_llm_calls));
+            }
+            return Ok(Verdict::NeedsReview("LLM call budget exhausted".to_string()));
+        }
+
+        let abstract_preview = Self::safe_truncate(abstract_text, 400);
+
+        let prompt = match (&self.prompt_template, self.args.search_mode) {
+            (Some(template), _) => Self::fill_prompt(template, subject, title, abstract_preview),
+            // A dataset has no findings to be "about" the topic, what matters is whether it's usable for it
+            (None, SearchMode::Topic) if is_dataset => format!(
+                "You are evaluating if a research dataset is useful for a research topic.\n\n\
+                Research Topic: \"{}\"\n\n\
+                Dataset Title: \"{}\"\n\n\
+                Description: \"{}\"\n\n\
+                Rate from 0.0 to 1.0 how useful this data would be for studying the topic and give a ONE to TWO sentence explanation.\n\n\
+                Respond with only a JSON object like this:\n\
+                {{\"score\": 0.85, \"reason\": \"The dataset contains labelled measurements of exactly the phenomenon studied.\"}}\n\n\
+                Be strict, only give high scores (0.85+) if the data directly covers the topic.",
+                subject, title, abstract_preview
+            ),
+            (None, SearchMode::Topic) => format!(
+                "You are evaluating if a scientific paper is relevant to a research topic.\n\n\
+                Research Topic: \"{}\"\n\n\
+                Paper Title: \"{}\"\n\n\
+                Abstract: \"{}\"\n\n\
+                Rate the relevance from 0.0 to 1.0 and give a ONE to TWO sentence explanation.\n\n\
+                Respond with only a JSON object like this:\n\
+                {{\"score\": 0.85, \"reason\": \"This paper directly addresses machine learning algorithms for classification tasks.\"}}\n\n\
+                Be very strict only give high scores (0.85+) if the paper is directly about the topic.",
+                subject, title, abstract_preview
+            ),
+            // Looking for a known paper, so the question is whether this is it, not whether it's on topic
+            (None, SearchMode::Title) => format!(
+                "You are checking whether a search result is a specific paper someone is looking for.\n\n\
+                Wanted Title: \"{}\"\n\n\
+                Result Title: \"{}\"\n\n\
+                Abstract: \"{}\"\n\n\
+                Rate from 0.0 to 1.0 how likely the result is the wanted paper (another version, preprint \
+                or translation of it still counts) and give a ONE to TWO sentence explanation.\n\n\
+                Respond with only a JSON object like this:\n\
+                {{\"score\": 0.95, \"reason\": \"The titles match apart from capitalisation.\"}}",
+                subject, title, abstract_preview
+            ),
+            (None, SearchMode::Author) => format!(
+                "You are checking whether a search result is a paper written by a specific author.\n\n\
+                Author: \"{}\"\n\n\
+                Paper Title: \"{}\"\n\n\
+                Abstract or snippet: \"{}\"\n\n\
+                Rate from 0.0 to 1.0 how likely the paper is by this author, judging from any names in the \
+                text, and give a ONE to TWO sentence explanation. The topic of the paper does not matter.\n\n\
+                Respond with only a JSON object like this:\n\
+                {{\"score\": 0.80, \"reason\": \"The snippet lists the author among the paper's authors.\"}}",
+                subject, title, abstract_preview
+            ),
+        };
+
+        // Past decisions are about topical relevance, they'd only confuse the title and author checks
+        let prompt = if self.args.search_mode == SearchMode::Topic {
+            format!("{}{}", self.few_shot, prompt)
+        } else {
+            prompt
+        };
+
+        let request = GenerationRequest::new(self.args.model.clone(), prompt.clone()).format(FormatType::Json);
+        
+        match ollama.generate(request).await {
+            Ok(response) => {
+                let text = response.response.trim();
+                
+                // JSON mode keeps most models to the object, the old SCORE/REASON parsing is
+                // still there for the ones that ignore it
+                let (mut score, mut reason) = match Self::parse_json_verdict(text) {
+                    Some((score, reason)) => (Some(score), reason),
+                    None => (Self::parse_score(text), Self::extract_reason(text)),
+                };
+
+                if score.is_none() && self.args.retry_unparsed && self.take_llm_call() {
+                    Self::log(&self.logger, "   [AI] Response contained no score, asking again for just the score");
+                    let strict = format!(
+                        "{}\n\nReply with ONLY the JSON object, for example:\n{{\"score\": 0.40, \"reason\": \"...\"}}\nNo other text.",
+                        prompt
+                    );
+                    let retry_request = GenerationRequest::new(self.args.model.clone(), strict).format(FormatType::Json);
+                    if let Ok(retry) = ollama.generate(retry_request).await {
+                        let retry_text = retry.response.trim();
+                        // The first reply's "reason" is the refusal or boilerplate, both come from the retry
+                        let verdict = Self::parse_json_verdict(retry_text)
+                            .or_else(|| Self::parse_score(retry_text).map(|score| (score, Self::extract_reason(retry_text))));
+                        if let Some((retry_score, retry_reason)) = verdict {
+                            score = Some(retry_score);
+                            reason = retry_reason;
+                        }
+                    }
+                }
+
+                // No label and no number at all means a refusal or boilerplate, not a 0.5
+                let score = match score {
+                    Some(score) => score,
+                    None => {
+                        Self::log(&self.logger, "   [AI] Response contained no score, flagging for review. Full response:");
+                        Self::log(&self.logger, &format!("   {}", text));
+                        return Ok(Verdict::NeedsReview(text.to_string()));
+                    }
+                };
+
+                let relevant = score >= min_score;
+                Ok(Verdict::Scored { relevant, score, reason })
+            }
+            Err(e) => {
+                Self::warn(&self.logger, &format!("  [AI] Error: {}", e));
+                Ok(Verdict::Scored { relevant: true, score: 0.7, reason: "AI error, accepted by default".to_string() })
+            }
+        }
+    }
+
+    // Embedding failures are treated like a failed LLM call, the paper is accepted by default
+    async fn score_by_embedding(&self, ollama: &Ollama, title: &str, abstract_text: &str, subject: &str, min_score: f32) -> Verdict {
+        let model = &self.args.embedding_model;
+        let cached = self.subject_embeddings.lock().unwrap().get(subject).cloned();
+        // Two papers scored side by side may both embed the subject the first time, harmless
+        let subject_embedding = match cached {
+            Some(embedding) => Ok(embedding),
+            None => embedding::embed(ollama, model, subject).await.inspect(|embedding| {
+                self.subject_embeddings.lock().unwrap().insert(subject.to_string(), embedding.clone());
+            }),
+        };
+        let paper_embedding = embedding::embed(ollama, model, &format!("{}\n\n{}", title, abstract_text)).await;
+
+        match (subject_embedding, paper_embedding) {
+            (Ok(subject), Ok(paper)) => {
+                let score = embedding::cosine_similarity(&subject, &paper).clamp(0.0, 1.0);
+                Verdict::Scored {
+                    relevant: score >= min_score,
+                    score,
+                    reason: format!("Embedding similarity {:.2} with {}", score, model),
+                }
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                Self::warn(&self.logger, &format!("  [AI] Embedding error: {}", e));
+                Verdict::Scored { relevant: true, score: 0.7, reason: "Embedding error, accepted by default".to_string() }
+            }
+        }
+    }
+
+    // Counts one call against --max-llm-calls, false once the budget is spent
+    fn take_llm_call(&self) -> bool {
+        self.args.max_llm_calls == 0 || self.llm_calls.fetch_add(1, Ordering::SeqCst) < self.args.max_llm_calls
+    }
+
+    // {"score": 0.85, "reason": "..."}, some models quote the number
+    fn parse_json_verdict(text: &str) -> Option<(f32, String)> {
+        #[derive(Deserialize)]
+        struct LlmVerdict {
+            score: serde_json::Value,
+            #[serde(default)]
+            reason: String,
+        }
+
+        let verdict: LlmVerdict = serde_json::from_str(text).ok()?;
+        let score = match &verdict.score {
+            serde_json::Value::Number(n) => n.as_f64()? as f32,
+            serde_json::Value::String(s) => s.trim().parse::<f32>().ok()?,
+            _ => return None,
+        };
+        Some((score, verdict.reason.trim().to_string()))
+    }
+
+    // The labeled SCORE line, else the first bare number anywhere in the response
+    fn parse_score(text: &str) -> Option<f32> {
+        let labeled_score = text.lines()
+            .find(|l| l.to_uppercase().contains("SCORE:"))
+            .and_then(|l| l.split(':').nth(1))
+            .and_then(|s| s.trim().parse::<f32>().ok());
+
+        labeled_score.or_else(|| text.split_whitespace().find_map(|word| word.parse::<f32>().ok()))
+    }
+
+    // Prefers the labeled REASON line; otherwise the last sentence-like line that isn't
+    // preamble ("Here is my evaluation:"), a sign-off ("Hope this helps!") or the score itself
+    fn extract_reason(text: &str) -> String {
+        let clean = |s: &str| s.trim().trim_matches(|c: char| c == '*' || c == '"').trim().to_string();
+        let lines: Vec<&str> = text.lines().map(|l| l.trim()).filter(|l| !l.is_empty()).collect();
+
+        if let Some(idx) = lines.iter().position(|l| l.to_uppercase().contains("REASON:")) {
+            let labeled = clean(&lines[idx].split(':').skip(1).collect::<Vec<_>>().join(":"));
+            if !labeled.is_empty() {
+                return labeled;
+            }
+            // Label on its own line, explanation underneath
+            if let Some(next) = lines.get(idx + 1) {
+                return clean(next);
+            }
+        }
+
+        let preamble_starts = ["here is", "here's", "sure", "okay", "ok,", "certainly", "based on", "my evaluation", "evaluation"];
+        let sign_off_starts = ["hope this helps", "i hope this helps", "let me know", "feel free", "please let me know"];
+        lines.iter()
+            .rev()
+            .filter(|l| !l.to_uppercase().contains("SCORE:"))
+            .filter(|l| !l.ends_with(':'))
+            .filter(|l| {
+                let lower = l.to_lowercase();
+                !preamble_starts.iter().chain(&sign_off_starts).any(|p| lower.starts_with(p))
+            })
+            .find(|l| l.split_whitespace().count() >= 3)
+            .map(|l| clean(l))
+            .unwrap_or_default()
+    }
+
+    // Every line logged for the paper carries its "[i/N]", see logs::for_paper. `total` is how
+    // many results this batch has, which with several subjects or --resume isn't --max-results
+    async fn process_result(&self, result: &SearchResult, index: usize, total: usize) -> Result<Option<ScientificPaper>> {
+        let tag = format!("[{}/{}]", index + 1, total);
+        logs::for_paper(tag, self.process_tagged(result)).await
+    }
+
+    async fn process_tagged(&self, result: &SearchResult) -> Result<Option<ScientificPaper>> {
+        if let Some(domain) = self.excluded_domain(&result.url) {
+            Self::debug(&self.logger, &format!("\nSKIPPED: {} is on an excluded domain ({})", result.url, domain));
+            return Ok(None);
+        }
+
+        Self::log(&self.logger, &format!("\n{}", "=".repeat(64)));
+        Self::log(&self.logger, &result.title);
+        Self::log(&self.logger, &format!("{}", "=".repeat(64)));
+        Self::log(&self.logger, &format!("URL: {}", result.url));
+        if !result.instances.is_empty() {
+            Self::log(&self.logger, &format!("Found via: {}", result.instances.join(", ")));
+        }
+        // Queues and dumps written before subjects were recorded have none
+        let subject = if result.subject.is_empty() { self.args.subjects().remove(0) } else { result.subject.clone() };
+        if self.args.subjects().len() > 1 {
+            Self::log(&self.logger, &format!("Subject: {}", subject));
+        }
+
+        // A paper without a DOI (arXiv-only, preprints) found again under another URL or by
+        // another engine is caught here by its title, before any page or API is fetched for it
+        if let Some(existing) = self.duplicate_of_saved(None, &result.title, &HashMap::new()) {
+            Self::log(&self.logger, &format!("SKIPPED: Already processed ({})\n", existing));
+            return Ok(None);
+        }
+
+        let mut doi = self.extract_doi_from_url(&result.url);
+
+        // Snippets often quote the DOI even when the URL is a publisher landing page
+        if doi.is_none() {
+            doi = self.extract_doi_from_snippet(&result.content);
+            if doi.is_some() {
+                Self::debug(&self.logger, "   [DOI] Found in the result snippet");
+            }
+        }
+
+        let trust = self.snippet_trust_for(&result.engine);
+        if trust != SnippetTrust::Normal {
+            Self::debug(&self.logger, &format!("   [SNIPPET] {:?} trust for engine '{}'", trust, result.engine));
+        }
+        // A trusted snippet is the abstract as far as we're concerned, an untrusted one is only
+        // a fallback for when the page and APIs come up empty
+        let trusted = trust == SnippetTrust::High && !result.content.trim().is_empty();
+        let mut abstract_text = if trust == SnippetTrust::Low { String::new() } else { result.content.clone() };
+        let mut title = result.title.clone();
+        let mut authors = Vec::new();
+        let mut keywords = Vec::new();
+        let mut cited_by = None;
+        let mut published = None;
+        let mut resource_type = String::new();
+        let mut page_fetched = false;
+        let mut api_fetched = false;
+        let mut identifiers = Self::identifiers_from_url(&result.url);
+
+        if let Some(record_id) = Self::openaire_id(&result.url) {
+            let started = Instant::now();
+            let record = self.fetch_openaire_metadata(&record_id).await;
+            self.timings.lock().unwrap().metadata += started.elapsed();
+            match record {
+                Ok(record) => {
+                    if doi.is_none() {
+                        doi = record.doi;
+                    }
+                    title = record.title;
+                    authors = record.authors;
+                    if !trusted && record.abstract_text.chars().count() > abstract_text.chars().count() {
+                        abstract_text = record.abstract_text;
+                    }
+                    keywords = record.keywords;
+                    published = record.published;
+                    resource_type = record.resource_type;
+                    Self::merge_identifiers(&mut identifiers, record.identifiers);
+                }
+                Err(e) => Self::warn(&self.logger, &format!("OpenAIRE lookup failed: {}", e)),
+            }
+        }
+
+        // PubMed and PMC pages carry the PMID or PMCID in the URL, E-utilities has the abstract
+        // and usually the DOI for it
+        if (identifiers.contains_key("pmid") || identifiers.contains_key("pmcid"))
+            && (doi.is_none() || (!trusted && abstract_text.chars().count() < self.args.min_abstract_chars))
+        {
+            let started = Instant::now();
+            let record = self.fetch_pubmed_metadata(&identifiers).await;
+            self.timings.lock().unwrap().metadata += started.elapsed();
+            match record {
+                Ok(record) => {
+                    if doi.is_none() {
+                        doi = record.doi;
+                    }
+                    if !record.title.is_empty() {
+                        title = record.title;
+                    }
+                    if !trusted && record.abstract_text.chars().count() > abstract_text.chars().count() {
+                        abstract_text = record.abstract_text;
+                    }
+                    if keywords.is_empty() {
+                        keywords = record.keywords;
+                    }
+                    authors = record.authors;
+                    published = record.published;
+                    Self::merge_identifiers(&mut identifiers, record.identifiers);
+                }
+                Err(e) => Self::warn(&self.logger, &format!("PubMed lookup failed: {}", e)),
+            }
+        }
+
+        // Characters, not bytes, a CJK abstract is three times longer in UTF-8
+        if doi.is_none() || (!trusted && abstract_text.chars().count() < self.args.min_abstract_chars) {
+            Self::debug(&self.logger, "   [FETCH] Scraping page for metadata");
+            page_fetched = true;
+            let started = Instant::now();
+            let page = self.fetch_page_content(&result.url).await;
+            self.timings.lock().unwrap().fetch += started.elapsed();
+            if let Ok(page) = page {
+                if doi.is_none() {
+                    doi = page.doi;
+                }
+                if !trusted && !page.abstract_text.is_empty() && page.abstract_text.chars().count() > abstract_text.chars().count() {
+                    abstract_text = page.abstract_text;
+                }
+                if keywords.is_empty() {
+                    keywords = page.keywords;
+                }
+                if authors.is_empty() {
+                    authors = page.authors;
+                }
+                if published.is_none() {
+                    published = page.published;
+                }
+                if resource_type.is_empty() {
+                    resource_type = page.resource_type;
+                }
+                Self::merge_identifiers(&mut identifiers, page.identifiers);
+            }
+        }
+
+        if let Some(ref doi_str) = doi {
+            Self::log(&self.logger, &format!("DOI: {}", doi_str));
+            
+            if self.args.dedup_scope != DedupScope::None && self.processed_dois.lock().unwrap().contains(doi_str) {
+                Self::log(&self.logger, "SKIPPED: Already processed\n");
+                return Ok(None);
+            }
+
+            if self.rejected_dois.lock().unwrap().contains(doi_str) {
+                Self::log(&self.logger, "SKIPPED: Rejected on a previous run\n");
+                return Ok(None);
+            }
+
+            if !trusted && abstract_text.chars().count() < self.args.min_abstract_chars {
+                Self::debug(&self.logger, "   [API] Fetching metadata from DOI APIs");
+                api_fetched = true;
+                let started = Instant::now();
+                let api = self.fetch_doi_metadata(doi_str).await;
+                self.timings.lock().unwrap().metadata += started.elapsed();
+                if let Ok(api) = api {
+                    if !api.title.is_empty() {
+                        title = api.title;
+                    }
+                    if !api.abstract_text.is_empty() && api.abstract_text.chars().count() > abstract_text.chars().count() {
+                        abstract_text = api.abstract_text;
+                    }
+                    if keywords.is_empty() {
+                        keywords = api.keywords;
+                    }
+                    // Registry author lists are in order and complete, meta tags sometimes aren't
+                    if !api.authors.is_empty() {
+                        authors = api.authors;
+                    }
+                    cited_by = api.cited_by;
+                    // Registry dates beat whatever the landing page put in its meta tags
+                    if api.published.is_some() {
+                        published = api.published;
+                    }
+                    if !api.resource_type.is_empty() {
+                        resource_type = api.resource_type;
+                    }
+                    Self::merge_identifiers(&mut identifiers, api.identifiers);
+                }
+            }
+
+            // The type filters need the type even when the snippet made the API lookup unnecessary
+            if (self.args.exclude_datasets || self.args.only_datasets || self.type_filter_active()) && !api_fetched {
+                api_fetched = true;
+                let started = Instant::now();
+                let api = self.fetch_doi_metadata(doi_str).await;
+                self.timings.lock().unwrap().metadata += started.elapsed();
+                if let Ok(api) = api
+                    && !api.resource_type.is_empty()
+                {
+                    resource_type = api.resource_type;
+                }
+            }
+        } else {
+            Self::log(&self.logger, "DOI: Not found");
+        }
+
+        if abstract_text.is_empty() {
+            abstract_text = result.content.clone();
+        }
+
+        if title.trim().is_empty() {
+            let slug = match self.args.on_missing_title {
+                MissingTitle::Skip => None,
+                MissingTitle::UrlSlug => Self::title_from_url(&result.url),
+            };
+            match slug {
+                Some(slug) => {
+                    Self::log(&self.logger, &format!("No title found, using the URL slug: {}", slug));
+                    title = slug;
+                }
+                None => {
+                    Self::log(&self.logger, "SKIPPED: No title from any source\n");
+                    return Ok(None);
+                }
+            }
+        }
+
+        // Whichever source won, the model and the output file should see plain text
+        abstract_text = Self::clean_abstract(&abstract_text);
+
+        if self.args.complete_truncated_abstracts && Self::looks_truncated(&abstract_text) {
+            let started = Instant::now();
+            let fuller = self.complete_abstract(&result.url, doi.as_deref(), page_fetched, api_fetched).await;
+            self.timings.lock().unwrap().metadata += started.elapsed();
+            if let Some(fuller) = fuller
+                && fuller.chars().count() > abstract_text.chars().count()
+            {
+                Self::log(&self.logger, &format!("Abstract looked truncated, replaced with a fuller one ({} -> {} chars)", abstract_text.chars().count(), fuller.chars().count()));
+                abstract_text = fuller;
+            }
+        }
+
+        // A lowered --min-abstract-chars also lets shorter abstracts stand in for the title
+        let abstract_chars = abstract_text.chars().count();
+        if abstract_chars > self.args.min_abstract_chars.min(50) {
+            Self::log(&self.logger, &format!("Abstract: {} chars", abstract_chars));
+            let preview = if abstract_chars > 200 {
+                format!("{}...", abstract_text.chars().take(200).collect::<String>())
+            } else {
+                abstract_text.clone()
+            };
+            Self::log(&self.logger, &format!("   \"{}\"", preview));
+        } else {
+            Self::log(&self.logger, "Abstract: None found (using title only)");
+            abstract_text = title.clone();
+        }
+
+        if !self.languages.is_empty()
+            && let Some((lang, confidence)) = language::detect(&abstract_text)
+        {
+            Self::debug(&self.logger, &format!("   [LANG] {} ({:.2} confidence)", lang.eng_name(), confidence));
+            if confidence >= self.args.lang_min_confidence && !self.languages.contains(&lang) {
+                Self::log(&self.logger, &format!("SKIPPED: Written in {} (--lang)\n", lang.eng_name()));
+                return Ok(None);
+            }
+        }
+
+        if let Some(doi_str) = &doi {
+            match doi_str.strip_prefix("arXiv:") {
+                Some(arxiv) => Self::add_identifier(&mut identifiers, "arxiv", arxiv),
+                None => Self::add_identifier(&mut identifiers, "doi", doi_str),
+            }
+        }
+
+        // The DOI was checked before enrichment, the title or a PMID or arXiv ID can give away
+        // the same paper found through another source
+        if let Some(existing) = self.duplicate_of_saved(None, &title, &identifiers) {
+            Self::log(&self.logger, &format!("SKIPPED: Already processed ({})\n", existing));
+            return Ok(None);
+        }
+
+        if identifiers.len() > 1 {
+            Self::log(&self.logger, &format!("IDs: {}", results::format_identifiers(&identifiers)));
+        }
+
+        if !authors.is_empty() {
+            Self::log(&self.logger, &format!("Authors: {}", authors.join("; ")));
+        }
+        if !keywords.is_empty() {
+            Self::log(&self.logger, &format!("Keywords: {}", keywords.join("; ")));
+        }
+        if let Some(count) = cited_by {
+            Self::log(&self.logger, &format!("Cited by: {}", count));
+        }
+        if let Some(date) = published {
+            Self::log(&self.logger, &format!("Published: {}", date));
+        }
+
+        // Undated papers are kept, most sources that omit a date aren't out of range
+        if let (Some((start, end)), Some(date)) = (self.date_bounds, published)
+            && (date < start || date > end)
+        {
+            Self::log(&self.logger, &format!("SKIPPED: Published {} is outside {} to {}\n", date, start, end));
+            return Ok(None);
+        }
+
+        if self.args.since_year.is_some() || self.args.until_year.is_some() {
+            match published.map(|date| date.year()) {
+                Some(year) if self.args.since_year.is_some_and(|since| year < since) || self.args.until_year.is_some_and(|until| year > until) => {
+                    Self::log(&self.logger, &format!("SKIPPED: Published in {}, outside {}\n", year, self.year_range()));
+                    return Ok(None);
+                }
+                None if !self.args.keep_undated => {
+                    Self::log(&self.logger, "SKIPPED: No publication year (--keep-undated keeps these)\n");
+                    return Ok(None);
+                }
+                _ => {}
+            }
+        }
+
+        // Dataset engines (openairedatasets, ...) say so even when there's no DOI to look up
+        if resource_type.is_empty() && result.engine.to_lowercase().contains("dataset") {
+            resource_type = "dataset".to_string();
+        }
+        let is_dataset = Self::is_dataset_type(&resource_type);
+        if !resource_type.is_empty() {
+            Self::log(&self.logger, &format!("Type: {}", resource_type));
+        }
+
+        if self.args.exclude_datasets && is_dataset {
+            Self::log(&self.logger, "SKIPPED: Dataset (--exclude-datasets)\n");
+            return Ok(None);
+        }
+        if self.args.only_datasets && !is_dataset {
+            Self::log(&self.logger, "SKIPPED: Not a dataset (--only-datasets)\n");
+            return Ok(None);
+        }
+
+        if let Some(reason) = self.type_rejection(&resource_type) {
+            Self::log(&self.logger, &format!("SKIPPED: {}\n", reason));
+            return Ok(None);
+        }
+
+        if !self.matches_required_keywords(&title, &abstract_text, &keywords) {
+            Self::log(&self.logger, "SKIPPED: None of the required keywords matched\n");
+            return Ok(None);
+        }
+
+        let oa_url = if self.args.unpaywall_email.trim().is_empty() {
+            None
+        } else {
+            let started = Instant::now();
+            let lookup = self.open_access_url(doi.as_deref(), &identifiers).await;
+            self.timings.lock().unwrap().metadata += started.elapsed();
+            match lookup {
+                Ok(Some(url)) => {
+                    Self::log(&self.logger, &format!("Open access: {}", url));
+                    Some(url)
+                }
+                Ok(None) if self.args.open_access_only => {
+                    Self::log(&self.logger, "SKIPPED: No open access copy (--open-access-only)\n");
+                    return Ok(None);
+                }
+                // Not knowing is not the same as free to read
+                Err(e) if self.args.open_access_only => {
+                    Self::warn(&self.logger, &format!("SKIPPED: Could not check open access ({})\n", e));
+                    return Ok(None);
+                }
+                Ok(None) => None,
+                Err(e) => {
+                    Self::debug(&self.logger, &format!("   [OA] {}", e));
+                    None
+                }
+            }
+        };
+
+        // Several engines agreeing on a result is evidence of relevance on its own
+        let engine_count = result.engine_count();
+        let min_score = if self.args.multi_engine_bonus > 0.0 && engine_count >= 2 {
+            (self.args.min_score - self.args.multi_engine_bonus).max(0.0)
+        } else {
+            self.args.min_score
+        };
+        if self.args.multi_engine_bonus > 0.0 {
+            Self::debug(&self.logger, &format!("   [ENGINES] Found by {} engine(s), min score {:.2}", engine_count, min_score));
+        }
+
+        let verdict = if self.use_ai {
+            Self::log(&self.logger, "\nAI Evaluation:");
+            let started = Instant::now();
+            let verdict = self.validate_with_ai(&title, &abstract_text, &subject, is_dataset, min_score).await;
+            self.timings.lock().unwrap().llm += started.elapsed();
+            verdict?
+        } else {
+            Verdict::Scored { relevant: true, score: 0.8, reason: "AI disabled".to_string() }
+        };
+
+        let (is_relevant, score, reason) = match verdict {
+            Verdict::Scored { relevant, score, reason } => (relevant, score, reason),
+            Verdict::NeedsReview(reason) => {
+                Self::log(&self.logger, "NEEDS REVIEW: AI gave no usable score");
+                return Ok(Some(ScientificPaper {
+                    title,
+                    url: result.url.clone(),
+                    doi,
+                    authors,
+                    abstract_text,
+                    keywords,
+                    cited_by,
+                    published,
+                    resource_type,
+                    engine: result.engine.clone(),
+                    identifiers,
+                    oa_url,
+                    subject,
+                    relevance_score: 0.0,
+                    reason,
+                    needs_review: true,
+                }));
+            }
+        };
+
+        Self::log(&self.logger, &format!("   Score: {:.2}/1.0", score));
+        Self::log(&self.logger, &format!("   Reason: {}", reason));
+
+        if is_relevant {
+            Self::log(&self.logger, "Relevant: Saving");
+        } else {
+            Self::log(&self.logger, "NOT Relevant: Skipping");
+        }
+
+        if !is_relevant {
+            if self.use_ai && self.args.remember_rejections && self.args.benchmark.is_empty() && !self.args.dry_run
+                && let Some(doi_str) = &doi
+            {
+                self.remember_rejection(doi_str);
+            }
+            return Ok(None);
+        }
+
+        Ok(Some(ScientificPaper {
+            title,
+            url: result.url.clone(),
+            doi,
+            authors,
+            abstract_text,
+            keywords,
+            cited_by,
+            published,
+            resource_type,
+            engine: result.engine.clone(),
+            identifiers,
+            oa_url,
+            subject,
+            relevance_score: score,
+            reason,
+            needs_review: false,
+        }))
+    }
+
+    // Where saved papers go, for the log
+    fn destination(&self) -> &str {
+        if self.db.is_some() { &self.args.db } else { &self.args.output }
+    }
+
+    // Which saved paper this one duplicates, as a description for the log. With --dedup-scope
+    // none nothing counts as a duplicate
+    fn duplicate_of_saved(&self, doi: Option<&str>, title: &str, identifiers: &HashMap<String, String>) -> Option<String> {
+        if self.args.dedup_scope == DedupScope::None {
+            return None;
+        }
+        if let Some(doi) = doi
+            && self.processed_dois.lock().unwrap().contains(doi)
+        {
+            return Some(format!("DOI {}", doi));
+        }
+        if let Some(existing) = self.processed_titles.lock().unwrap().find_similar(title) {
+            return Some(format!("title matches \"{}\"", existing));
+        }
+        let ids = self.processed_ids.lock().unwrap();
+        Self::identifier_keys(identifiers).find(|key| ids.contains(key))
+    }
+
+    fn save_doi(&self, paper: &ScientificPaper) -> Result<PaperResult> {
+        let record = paper.to_result();
+        let sync = self.args.fsync == FsyncPolicy::PerPaper;
+        match (&self.db, self.args.format) {
+            (Some(db), _) => {
+                if !db.insert(&record)? {
+                    Self::log(&self.logger, "Already in the database, kept the stored copy");
+                }
+            }
+            (None, format) => Self::write_record(&self.args.output, &record, format, self.args.output_bom, sync)?,
+        }
+
+        // Only once it's written, a paper whose save failed gets another go on the next run
+        if let Some(doi) = &paper.doi
+            && let Err(e) = self.processed_dois.lock().unwrap().insert(doi)
+        {
+            Self::error(&self.logger, &format!("Could not record {} as processed: {}", doi, e));
+        }
+        if let Err(e) = self.processed_titles.lock().unwrap().insert(&paper.title) {
+            Self::error(&self.logger, &format!("Could not record \"{}\" as processed: {}", paper.title, e));
+        }
+        for key in Self::identifier_keys(&paper.identifiers) {
+            if let Err(e) = self.processed_ids.lock().unwrap().insert(&key) {
+                Self::error(&self.logger, &format!("Could not record {} as processed: {}", key, e));
+            }
+        }
+
+        // No listeners is fine, send only fails when nobody is subscribed
+        if let Some(events) = &self.paper_events
+            && let Ok(json) = serde_json::to_string(&record)
+        {
+            let _ = events.send(json);
+        }
+
+        Self::log(&self.logger, &format!("SAVED to: {}", self.destination()));
+
+        if let Some(template) = &self.template
+            && let Err(e) = template.append(&record)
+        {
+            Self::warn(&self.logger, &format!("Could not render template into {}: {}", template.output(), e));
+        }
+
+        Ok(record)
+    }
+
+    fn remember_rejection(&self, doi: &str) {
+        self.rejected_dois.lock().unwrap().insert(doi.to_string());
+
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.args.rejections_file)
+            .and_then(|mut file| writeln!(file, "{}", doi));
+        if let Err(e) = written {
+            Self::warn(&self.logger, &format!("Could not remember rejection: {}", e));
+        }
+    }
+
+    // Not added to processed_dois, so the next run gets another go at scoring it
+    fn save_for_review(&self, paper: &ScientificPaper) -> Result<()> {
+        Self::write_record(&self.args.review_output, &paper.to_result(), OutputFormat::Text, self.args.output_bom, self.args.fsync == FsyncPolicy::PerPaper)?;
+
+        Self::log(&self.logger, &format!("Flagged for review in: {}", self.args.review_output));
+        Ok(())
+    }
+
+    fn write_record(path: &str, record: &PaperResult, format: OutputFormat, bom: bool, sync: bool) -> Result<()> {
+        let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+
+        // Only the very start of a file may carry one, anywhere else it's a stray character.
+        // Excel needs it to read a CSV as UTF-8
+        if file.metadata()?.len() == 0 {
+            if bom && matches!(format, OutputFormat::Text | OutputFormat::Csv) {
+                write!(file, "{}", results::BOM)?;
+            }
+            write!(file, "{}", results::file_header(format, &record.subject))?;
+        }
+        write!(file, "{}", results::serialize_paper(record, format))?;
+        if sync {
+            file.sync_all()?;
+        }
+        Ok(())
+    }
+
+    // Any handle will do, fsync flushes the file's pending writes whoever made them.
+    // A file that doesn't exist yet has nothing to lose
+    fn sync_file(path: &str) -> std::io::Result<()> {
+        match fs::File::open(path) {
+            Ok(file) => file.sync_all(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn spawn_sync_task(&self) -> BackgroundTask {
+        let paths = [self.args.output.clone(), self.args.review_output.clone()];
+        let interval = Duration::from_secs(self.args.fsync_interval_secs.max(1));
+        let logger = self.logger.clone();
+        BackgroundTask(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                for path in &paths {
+                    if let Err(e) = Self::sync_file(path) {
+                        Self::warn(&logger, &format!("Could not sync {}: {}", path, e));
+                    }
+                }
+            }
+        }))
+    }
+
+    // The first Ctrl-C lets the papers already being processed finish and be saved, the second
+    // one doesn't wait
+    fn spawn_interrupt_watcher(&self) -> BackgroundTask {
+        let interrupted = self.interrupted.clone();
+        let logger = self.logger.clone();
+        BackgroundTask(tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_err() {
+                return;
+            }
+            interrupted.store(true, Ordering::SeqCst);
+            Self::log(&logger, "\nInterrupted, finishing the papers in progress (Ctrl-C again to quit now)");
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(130);
+            }
+        }))
+    }
+
+    fn interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::SeqCst)
+    }
+
+    async fn run_save_hook(&self, record: &PaperResult) -> Result<()> {
+        if self.args.on_save_command.is_empty() {
+            return Ok(());
+        }
+
+        let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+        let mut child = tokio::process::Command::new(shell)
+            .arg(flag)
+            .arg(&self.args.on_save_command)
+            .stdin(std::process::Stdio::piped())
+            .spawn()?;
+
+        let json = serde_json::to_string(record)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            use tokio::io::AsyncWriteExt;
+            // A script that never reads stdin closes the pipe early, its exit status is what counts
+            let _ = stdin.write_all(json.as_bytes()).await;
+            // Dropping stdin closes it so the script sees EOF
+        }
+
+        let status = child.wait().await?;
+        if !status.success() {
+            return Err(anyhow!("'{}' exited with {}", self.args.on_save_command, status));
+        }
+        Self::debug(&self.logger, &format!("   [HOOK] '{}' exited with {}", self.args.on_save_command, status));
+        Ok(())
+    }
+
+    async fn download_pdf(&self, record: &PaperResult) {
+        if !self.args.download_pdf {
+            return;
+        }
+        let (Some(url), Some(name)) = (pdf::pdf_url(record), pdf::file_name(record)) else {
+            Self::log(&self.logger, "PDF skipped: no free copy known");
+            return;
+        };
+
+        self.domain_limiter.wait(&url).await;
+        match pdf::download(&self.client, &url, &self.args.pdf_dir, &name).await {
+            Ok(pdf::Download::Saved(bytes)) => Self::log(&self.logger, &format!("PDF saved: {} ({} KB)", name, bytes / 1024)),
+            Ok(pdf::Download::AlreadyThere) => Self::log(&self.logger, &format!("PDF already downloaded: {}", name)),
+            Ok(pdf::Download::NotPdf(content_type)) => Self::log(&self.logger, &format!("PDF skipped: {} is {}, not a PDF", url, content_type)),
+            Err(e) => Self::warn(&self.logger, &format!("PDF download failed: {}", e)),
+        }
+    }
+
+    async fn export_to_notion(&self, paper: &ScientificPaper) {
+        let Some(notion) = &self.notion else {
+            return;
+        };
+
+        match notion.export(paper).await {
+            Ok(page_id) => Self::log(&self.logger, &format!("Notion page created: {}", page_id)),
+            Err(e) => Self::log(&self.logger, &format!("Notion export skipped: {}", e)),
+        }
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
+        let started = Instant::now();
+        let started_at = chrono::Local::now();
+        if !self.args.benchmark.is_empty() {
+            return self.run_benchmark().await;
+        }
+        if self.args.dry_run {
+            return self.run_dry().await;
+        }
+
+        // Dedup state was loaded from the old file in the constructor, so overwriting keeps
+        // skipping those papers unless --reset-dedup says otherwise
+        match self.args.output_mode {
+            OutputMode::Append => {
+                // Two formats in one file would leave neither readable
+                if self.db.is_none()
+                    && let Ok(existing) = fs::read_to_string(&self.args.output)
+                    && !existing.trim_start_matches(results::BOM).trim().is_empty()
+                    && results::detect_format(&existing) != self.args.format
+                {
+                    return Err(anyhow!("{} is in another format than --format {:?}, choose another --output or use --output-mode overwrite", self.args.output, self.args.format));
+                }
+                Self::log(&self.logger, &format!("Output mode: appending to {}", self.destination()));
+                if self.args.reset_dedup {
+                    Self::log(&self.logger, "--reset-dedup ignored in append mode, it would write duplicates into the existing file");
+                }
+            }
+            OutputMode::Overwrite => {
+                match &self.db {
+                    Some(db) => db.clear()?,
+                    None => fs::write(&self.args.output, "")?,
+                }
+                Self::log(&self.logger, &format!("Output mode: overwriting {}", self.destination()));
+                if self.args.reset_dedup {
+                    self.processed_dois.get_mut().unwrap().clear()?;
+                    self.processed_titles.get_mut().unwrap().clear()?;
+                    self.processed_ids.get_mut().unwrap().clear()?;
+                    Self::log(&self.logger, "Dedup reset, previously saved papers can be saved again");
+                } else if self.args.dedup_scope == DedupScope::Persistent {
+                    Self::log(&self.logger, "Papers from the old file are still skipped (use --reset-dedup to start clean)");
+                }
+            }
+        }
+
+        // SQLite takes care of its own durability
+        let sync_task = (self.args.fsync == FsyncPolicy::Interval && self.db.is_none()).then(|| self.spawn_sync_task());
+        // The web server runs its searches through here too, Ctrl-C there should still stop the server
+        let _interrupt_watcher = self.progress.is_none().then(|| self.spawn_interrupt_watcher());
+
+        let queue = WorkQueue::new(&self.args.queue_file);
+        let pending = queue.pending()?;
+        let mut stats = RunStats::default();
+
+        if self.args.resume && !pending.is_empty() {
+            Self::log(&self.logger, &format!("Resuming {} unfinished results from {}", pending.len(), self.args.queue_file));
+            self.process_all(&queue, &pending, &mut stats).await?;
+        } else {
+            if self.args.resume {
+                Self::log(&self.logger, "Nothing to resume, starting a fresh search");
+            } else if !pending.is_empty() {
+                Self::log(&self.logger, &format!("Discarding {} unfinished results from a previous run (use --resume to keep them)", pending.len()));
+            }
+
+            if self.args.stream_results {
+                self.process_streaming(&queue, &mut stats).await?;
+            } else {
+                let results_to_process = self.search_subjects().await?;
+
+                if !self.args.dump_search.is_empty() {
+                    bench::save_dump(&self.args.dump_search, &results_to_process)?;
+                    Self::log(&self.logger, &format!("Saved {} search results to {}", results_to_process.len(), self.args.dump_search));
+                }
+
+                queue.reset()?;
+                for result in &results_to_process {
+                    queue.enqueue(result)?;
+                }
+                self.process_all(&queue, &results_to_process, &mut stats).await?;
+            }
+        }
+
+        // Whatever wasn't processed stays queued for --resume
+        let interrupted = self.interrupted();
+        if !interrupted {
+            queue.remove();
+        }
+
+        if self.args.sort_by != SortBy::None && self.db.is_some() {
+            Self::log(&self.logger, "--sort-by only reorders results files, the database is left as it is");
+        } else if self.args.sort_by != SortBy::None {
+            match results::sort_file(&self.args.output, self.args.sort_by) {
+                Ok(()) => Self::log(&self.logger, &format!("Sorted {} by {:?}", self.args.output, self.args.sort_by)),
+                Err(e) => Self::warn(&self.logger, &format!("Could not sort output: {}", e)),
+            }
+        }
+
+        if !self.args.export_full.is_empty() {
+            let exported = match &self.db {
+                Some(db) => db.papers(None).and_then(|papers| results::export_papers(papers, &self.args.export_full)),
+                None => results::export_full(&self.args.output, &self.args.export_full),
+            };
+            match exported {
+                Ok(count) => Self::log(&self.logger, &format!("Exported {} papers to {}", count, self.args.export_full)),
+                Err(e) => Self::warn(&self.logger, &format!("Could not write full export: {}", e)),
+            }
+        }
+
+        // One last sync so whatever the final interval missed (and the sort rewrite) is on disk
+        if let Some(task) = sync_task {
+            drop(task);
+            for path in [&self.args.output, &self.args.review_output] {
+                if let Err(e) = Self::sync_file(path) {
+                    Self::warn(&self.logger, &format!("Could not sync {}: {}", path, e));
+                }
+            }
+        }
+
+        Self::log(&self.logger, &format!("\n{}", "=".repeat(64)));
+        Self::log(&self.logger, if interrupted { "Results (interrupted)" } else { "Results" });
+        Self::log(&self.logger, &format!("{}", "=".repeat(64)));
+        Self::log(&self.logger, &format!("Total processed: {}", stats.processed));
+        Self::log(&self.logger, &format!("Validated as relevant: {}", stats.validated));
+        Self::log(&self.logger, &format!("Saved to file: {}", stats.saved));
+        Self::log(&self.logger, &format!("Skipped: {}", stats.skipped));
+        if stats.needs_review > 0 {
+            Self::log(&self.logger, &format!("Needs review: {} (see {})", stats.needs_review, self.args.review_output));
+        }
+        let unscored = self.llm_unscored.load(Ordering::SeqCst);
+        if unscored > 0 {
+            Self::log(&self.logger, &format!("Unscored (LLM budget of {} spent): {}", self.args.max_llm_calls, unscored));
+        }
+        Self::log(&self.logger, &format!("Output: {}\n", self.destination()));
+
+        if !self.args.summary_json.is_empty() {
+            let summary = RunSummary {
+                subject: &self.args.subjects().join(", "),
+                total: stats.processed,
+                validated: stats.validated,
+                saved: stats.saved,
+                skipped: stats.skipped,
+                duration_secs: started.elapsed().as_secs_f64(),
+                started_at: started_at.to_rfc3339(),
+                finished_at: chrono::Local::now().to_rfc3339(),
+            };
+            let written = serde_json::to_string_pretty(&summary)
+                .map_err(anyhow::Error::from)
+                .and_then(|json| Ok(fs::write(&self.args.summary_json, json + "\n")?));
+            match written {
+                Ok(()) => Self::log(&self.logger, &format!("Run summary written to {}", self.args.summary_json)),
+                Err(e) => Self::warn(&self.logger, &format!("Could not write run summary: {}", e)),
+            }
+        }
+
+        if interrupted {
+            return Err(anyhow!("Interrupted, the unprocessed results are kept in {} for --resume", self.args.queue_file));
+        }
+        Ok(())
+    }
+
+    // --dry-run: the usual search and scoring, with every paper that passes kept for the table
+    // at the end instead of going through handle_outcome
+    async fn run_dry(&self) -> Result<()> {
+        Self::log(&self.logger, "Dry run: nothing will be saved");
+        let (candidates, processed) = self.validate_results().await?;
+
+        let saved = candidates.iter().filter(|p| !p.needs_review).count();
+        Self::log(&self.logger, &format!("\n{}", "=".repeat(64)));
+        Self::log(&self.logger, &format!("Dry run: {} of {} results would be saved", saved, processed));
+        Self::log(&self.logger, &"=".repeat(64));
+        if candidates.is_empty() {
+            return Ok(());
+        }
+        Self::log(&self.logger, &format!("{:<32} {:>6}  Title", "DOI", "Score"));
+        for paper in &candidates {
+            // Papers without a usable score would go to --review-output
+            let score = if paper.needs_review { "review".to_string() } else { format!("{:.2}", paper.relevance_score) };
+            let doi = paper.doi.as_deref().unwrap_or("NA");
+            Self::log(&self.logger, &format!("{:<32} {:>6}  {}", doi, score, Self::safe_truncate(&paper.title, 80)));
+        }
+        Ok(())
+    }
+
+    // For programs using Researcher as a library: the usual search and scoring, with the papers
+    // that pass handed back instead of written anywhere. Highest score first, the ones a run
+    // would send to --review-output have needs_review set
+    pub async fn search_and_validate(&mut self) -> Result<Vec<ScientificPaper>> {
+        let (papers, _) = self.validate_results().await?;
+        Ok(papers)
+    }
+
+    // Searches and scores every result, keeping what passes. Also gives how many were processed
+    async fn validate_results(&self) -> Result<(Vec<ScientificPaper>, usize)> {
+        let results = self.search_subjects().await?;
+        Self::log(&self.logger, &format!("\nProcessing results: {} ({} at a time)\n", results.len(), self.args.concurrency.max(1)));
+
+        self.update_progress(|p| p.total = results.len());
+        let results = &results;
+        // Indices for the same reason as in run_benchmark
+        let mut outcomes = stream::iter(0..results.len())
+            .map(|i| async move { self.process_result(&results[i], i, results.len()).await })
+            .buffer_unordered(self.args.concurrency.max(1));
+
+        let mut candidates = Vec::new();
+        let mut processed = 0;
+        while let Some(outcome) = outcomes.next().await {
+            processed += 1;
+            match outcome {
+                Ok(Some(paper)) => candidates.push(paper),
+                Ok(None) => {}
+                Err(e) => Self::error(&self.logger, &format!("An error occured: {}", e)),
+            }
+            self.update_progress(|p| p.processed = processed);
+        }
+
+        candidates.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
+        Ok((candidates, processed))
+    }
+
+    // Same pipeline as a real run minus the search and the saving, every paper in the dump is
+    // processed again even if an earlier run saved it
+    async fn run_benchmark(&mut self) -> Result<()> {
+        let dump: Vec<SearchResult> = bench::load_dump(&self.args.benchmark)?
+            .into_iter()
+            .take(self.args.max_results)
+            .collect();
+        Self::log(&self.logger, &format!("Benchmark: {} results from {}, nothing will be saved", dump.len(), self.args.benchmark));
+
+        // Fresh in-memory sets, a --dedup-db database is left as it was
+        *self.processed_dois.get_mut().unwrap() = KeyStore::memory(HashSet::new());
+        *self.processed_titles.get_mut().unwrap() = TitleIndex::new(self.args.title_dedup_threshold);
+        *self.processed_ids.get_mut().unwrap() = KeyStore::memory(HashSet::new());
+        self.rejected_dois.get_mut().unwrap().clear();
+        *self.timings.get_mut().unwrap() = PhaseTimes::default();
+
+        let started = Instant::now();
+        let this = &*self;
+        let dump = &dump;
+        // Indices rather than references into the dump, a closure taking a borrowed item
+        // trips the compiler's Send check once the scraper runs inside tokio::spawn
+        let latencies: Vec<Duration> = stream::iter(0..dump.len())
+            .map(|i| async move {
+                let paper_started = Instant::now();
+                if let Err(e) = this.process_result(&dump[i], i, dump.len()).await {
+                    Self::error(&this.logger, &format!("An error occured: {}", e));
+                }
+                paper_started.elapsed()
+            })
+            .buffer_unordered(self.args.concurrency.max(1))
+            .collect()
+            .await;
+
+        let report = bench::Report {
+            total: started.elapsed(),
+            latencies,
+            phases: std::mem::take(self.timings.get_mut().unwrap()),
+        };
+
+        Self::log(&self.logger, &format!("\n{}", "=".repeat(64)));
+        Self::log(&self.logger, "Benchmark");
+        Self::log(&self.logger, &"=".repeat(64));
+        for line in report.lines() {
+            Self::log(&self.logger, &line);
+        }
+        Ok(())
+    }
+
+    async fn process_all(&self, queue: &WorkQueue, results: &[SearchResult], stats: &mut RunStats) -> Result<()> {
+        Self::log(&self.logger, &format!("\nProcessing results: {} ({} at a time)\n", results.len(), self.args.concurrency.max(1)));
+
+        // Indices for the same reason as in run_benchmark
+        self.update_progress(|p| p.total = results.len());
+        let mut outcomes = stream::iter(0..results.len())
+            .take_while(|_| futures::future::ready(!self.interrupted()))
+            .map(|i| async move { (&results[i], self.process_result(&results[i], i, results.len()).await) })
+            .buffer_unordered(self.args.concurrency.max(1));
+
+        let mut after_save = AfterSave::new();
+        loop {
+            tokio::select! {
+                Some((result, outcome)) = outcomes.next() => {
+                    self.handle_outcome(outcome, stats, &mut after_save)?;
+                    queue.mark_done(&result.url)?;
+                    self.update_progress(|p| (p.processed, p.saved, p.skipped) = (stats.processed, stats.saved, stats.skipped));
+                }
+                Some(done) = after_save.next() => done?,
+                else => break,
+            }
+        }
+        Ok(())
+    }
+
+    // Up to --max-results for each subject, in subject order. A URL another subject already
+    // found is left to that one
+    async fn search_subjects(&self) -> Result<Vec<SearchResult>> {
+        let subjects = self.args.subjects();
+        let mut results = Vec::new();
+        let mut seen = HashSet::new();
+        let mut failures = 0;
+        for subject in &subjects {
+            if self.interrupted() {
+                break;
+            }
+            if subjects.len() > 1 {
+                Self::log(&self.logger, &format!("\nSearching for subject: {}", subject));
+            }
+            match self.searcher.for_subject(subject).search().await {
+                Ok(found) => {
+                    let found: Vec<SearchResult> = found.into_iter()
+                        .filter(|result| seen.insert(result.url_key()))
+                        .collect();
+                    if self.args.balance_engines {
+                        results.extend(self.interleave_engines(found));
+                    } else {
+                        results.extend(found.into_iter().take(self.args.max_results));
+                    }
+                }
+                Err(e) if subjects.len() == 1 => return Err(e),
+                Err(e) => {
+                    Self::warn(&self.logger, &format!("Search for '{}' failed: {}", subject, e));
+                    failures += 1;
+                }
+            }
+        }
+        if failures == subjects.len() {
+            return Err(anyhow!("The searches for all {} subjects failed", subjects.len()));
+        }
+        Ok(results)
+    }
+
+    // One result from each engine in turn, engines in the order they first show up and each
+    // one's results in SearXNG's order, until --max-results are picked
+    fn interleave_engines(&self, found: Vec<SearchResult>) -> Vec<SearchResult> {
+        let mut by_engine: Vec<(String, std::collections::VecDeque<SearchResult>)> = Vec::new();
+        for result in found {
+            let engine = if result.engine.is_empty() { "unknown".to_string() } else { result.engine.to_lowercase() };
+            match by_engine.iter_mut().find(|(name, _)| *name == engine) {
+                Some((_, queue)) => queue.push_back(result),
+                None => by_engine.push((engine, std::collections::VecDeque::from([result]))),
+            }
+        }
+
+        let counts: Vec<String> = by_engine.iter().map(|(engine, queue)| format!("{} {}", engine, queue.len())).collect();
+        let mut picked = Vec::new();
+        while picked.len() < self.args.max_results && by_engine.iter().any(|(_, queue)| !queue.is_empty()) {
+            for (_, queue) in by_engine.iter_mut() {
+                if picked.len() == self.args.max_results {
+                    break;
+                }
+                if let Some(result) = queue.pop_front() {
+                    picked.push(result);
+                }
+            }
+        }
+        Self::log(&self.logger, &format!("Balanced {} results across engines ({})", picked.len(), counts.join(", ")));
+        picked
+    }
+
+    // The search runs in its own task and each pass's results are processed as they arrive,
+    // so the first scores show up while later years/pages are still being fetched
+    async fn process_streaming(&self, queue: &WorkQueue, stats: &mut RunStats) -> Result<()> {
+        queue.reset()?;
+        Self::log(&self.logger, &format!("\nProcessing results as they arrive ({} at a time)\n", self.args.concurrency.max(1)));
+
+        // How many results the search will find isn't known until it's done
+        let subjects = self.args.subjects();
+        self.update_progress(|p| p.total = self.args.max_results * subjects.len());
+        let mut seen = HashSet::new();
+        let mut failures = 0;
+        for subject in &subjects {
+            if self.interrupted() {
+                break;
+            }
+            if subjects.len() > 1 {
+                Self::log(&self.logger, &format!("\nSearching for subject: {}", subject));
+            }
+            match self.stream_subject(subject, queue, stats, &mut seen).await {
+                Err(e) if subjects.len() == 1 => return Err(e),
+                Err(e) => {
+                    Self::warn(&self.logger, &format!("Search for '{}' failed: {}", subject, e));
+                    failures += 1;
+                }
+                Ok(()) => {}
+            }
+        }
+        if failures == subjects.len() {
+            return Err(anyhow!("The searches for all {} subjects failed", subjects.len()));
+        }
+        Ok(())
+    }
+
+    // One subject's share of process_streaming. `seen` carries the URLs earlier subjects took
+    async fn stream_subject(&self, subject: &str, queue: &WorkQueue, stats: &mut RunStats, seen: &mut HashSet<String>) -> Result<()> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let searcher = self.searcher.for_subject(subject);
+        let search = tokio::spawn(async move { searcher.search_batches(tx).await });
+
+        let mut outcomes = UnboundedReceiverStream::new(rx)
+            .flat_map(stream::iter)
+            // Too late to pool engines for a URL that may already be scored, repeats are just dropped
+            .filter(|result: &SearchResult| futures::future::ready(seen.insert(result.url_key())))
+            .take(self.args.max_results)
+            .take_while(|_| futures::future::ready(!self.interrupted()))
+            .enumerate()
+            .map(|(i, result)| async move {
+                if let Err(e) = queue.enqueue(&result) {
+                    Self::warn(&self.logger, &format!("Could not queue {}: {}", result.url, e));
+                }
+                // Still arriving, --max-results is as many as this subject can have
+                let outcome = self.process_result(&result, i, self.args.max_results).await;
+                (result, outcome)
+            })
+            .buffer_unordered(self.args.concurrency.max(1));
+
+        let mut processed = 0;
+        let mut after_save = AfterSave::new();
+        loop {
+            tokio::select! {
+                Some((result, outcome)) = outcomes.next() => {
+                    self.handle_outcome(outcome, stats, &mut after_save)?;
+                    queue.mark_done(&result.url)?;
+                    processed += 1;
+                    self.update_progress(|p| (p.processed, p.saved, p.skipped) = (stats.processed, stats.saved, stats.skipped));
+                }
+                Some(done) = after_save.next() => done?,
+                else => break,
+            }
+        }
+
+        if processed >= self.args.max_results || self.interrupted() {
+            search.abort();
+            return Ok(());
+        }
+
+        match search.await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) if processed == 0 => Err(e),
+            Ok(Err(e)) => {
+                Self::log(&self.logger, &format!("Search ended early: {}", e));
+                Ok(())
+            }
+            Err(e) => Err(anyhow!("search task failed: {}", e)),
+        }
+    }
+
+    // Outcomes arrive one at a time, so saves stay serialized even with --concurrency. Two
+    // copies of one paper can pass the checks in process_result side by side, the second is
+    // caught here. The slow part after a save goes into `after_save`, which the caller polls
+    // next to the pipeline so a PDF download doesn't hold up the papers still being scored
+    fn handle_outcome<'a>(&'a self, outcome: Result<Option<ScientificPaper>>, stats: &mut RunStats, after_save: &mut AfterSave<'a>) -> Result<()> {
+        stats.processed += 1;
+        match outcome {
+            Ok(Some(paper)) if paper.needs_review => {
+                stats.needs_review += 1;
+                if let Err(e) = self.save_for_review(&paper) {
+                    Self::error(&self.logger, &format!("Could not write review entry: {}", e));
+                }
+            }
+            Ok(Some(paper)) => {
+                if let Some(existing) = self.duplicate_of_saved(paper.doi.as_deref(), &paper.title, &paper.identifiers) {
+                    Self::log(&self.logger, &format!("SKIPPED: {} was saved while it was being scored ({})", paper.title, existing));
+                    stats.skipped += 1;
+                    return Ok(());
+                }
+                stats.validated += 1;
+                match self.save_doi(&paper) {
+                    Ok(record) => {
+                        stats.saved += 1;
+                        after_save.push(Box::pin(self.after_save(record, paper)));
+                    }
+                    Err(e) => Self::error(&self.logger, &format!("Could not save {}: {}", paper.title, e)),
+                }
+            }
+            Ok(None) => {
+                stats.skipped += 1;
+            }
+            Err(e) => {
+                Self::error(&self.logger, &format!("An error occured: {}", e));
+            }
+        }
+        Ok(())
+    }
+
+    // PDF, Notion and the on-save command for one saved paper. Only errors when --hook-fatal
+    // says a failed hook should end the run
+    async fn after_save(&self, record: PaperResult, paper: ScientificPaper) -> Result<()> {
+        self.download_pdf(&record).await;
+        self.export_to_notion(&paper).await;
+        if let Err(e) = self.run_save_hook(&record).await {
+            if self.args.hook_fatal {
+                return Err(e);
+            }
+            Self::warn(&self.logger, &format!("On-save command failed: {}", e));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // No AI and no dedup, so nothing outside the test is read or contacted
+    async fn scraper() -> DOIScraper {
+        let args = Args::parse_from(["researcher", "--no-ai", "--dedup-scope", "none", "--cache-dir", ""]);
+        DOIScraper::new(args).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn body_doi_prefers_the_one_in_the_url() {
+        let scraper = scraper().await;
+        let page = Html::parse_document(
+            "<html><body><h1>Folding kinetics</h1>\
+            <p>DOI: 10.5555/target.7</p>\
+            <ol class='references'>\
+            <li>Smith, doi: 10.1000/ref.1</li><li>Jones, doi: 10.1000/ref.1</li>\
+            <li>Lee, https://doi.org/10.1000/ref.1</li><li>Kim, doi: 10.1000/ref.2</li>\
+            </ol></body></html>",
+        );
+        assert_eq!(scraper.pick_body_doi(&page, "https://pub.example/doi/10.5555/target.7").as_deref(), Some("10.5555/target.7"));
+        // Without the URL to go on, the most repeated labelled DOI wins
+        assert_eq!(scraper.pick_body_doi(&page, "https://pub.example/article/7").as_deref(), Some("10.1000/ref.1"));
+    }
+
+    #[tokio::test]
+    async fn body_doi_ignores_repeated_unlabelled_numbers() {
+        let scraper = scraper().await;
+        let page = Html::parse_document(
+            "<html><body>\
+            <p>Funded under grant 10.13039/501100000780.</p>\
+            <p>Acknowledgements: grant 10.13039/501100000780 and again 10.13039/501100000780.</p>\
+            <p>Cite as: DOI: 10.1234/paper.42</p>\
+            </body></html>",
+        );
+        assert_eq!(scraper.pick_body_doi(&page, "https://pub.example/article/42").as_deref(), Some("10.1234/paper.42"));
+    }
+
+    #[tokio::test]
+    async fn body_doi_ties_go_to_the_first_one() {
+        let scraper = scraper().await;
+        let page = Html::parse_document("<p>doi: 10.1000/first</p><p>doi: 10.1000/second</p>");
+        assert_eq!(scraper.pick_body_doi(&page, "https://pub.example/x").as_deref(), Some("10.1000/first"));
+        let unlabelled = Html::parse_document("<p>Reference number 10.1000/first</p>");
+        assert_eq!(scraper.pick_body_doi(&unlabelled, "https://pub.example/x"), None);
+    }
+
+    #[test]
+    fn reason_label_wins_over_chatter_around_it() {
+        let reply = "Sure! Here is my evaluation of the paper:\n\n\
+            **SCORE:** 0.85\n\
+            **REASON:** The paper measures protein folding rates directly.\n\n\
+            I hope this helps, let me know if you need anything else!";
+        assert_eq!(DOIScraper::extract_reason(reply), "The paper measures protein folding rates directly.");
+        assert_eq!(DOIScraper::parse_score(reply), Some(0.85));
+    }
+
+    #[test]
+    fn reason_on_the_line_under_its_label() {
+        let reply = "Okay, let me think about this.\nSCORE: 0.4\nREASON:\nOnly mentions folding in passing.\nThat's my assessment overall.";
+        assert_eq!(DOIScraper::extract_reason(reply), "Only mentions folding in passing.");
+    }
+
+    #[test]
+    fn unlabeled_reason_skips_preamble_score_and_sign_off() {
+        let reply = "Here is my evaluation:\n\
+            Based on the title and abstract, I would rate it as follows.\n\
+            SCORE: 0.7\n\
+            The study is about folding kinetics of small proteins.\n\
+            Hope this helps!";
+        assert_eq!(DOIScraper::extract_reason(reply), "The study is about folding kinetics of small proteins.");
+    }
+
+    #[tokio::test]
+    async fn snippet_doi_drops_trailing_punctuation() {
+        let scraper = scraper().await;
+        assert_eq!(scraper.extract_doi_from_snippet("Published 2021, doi: 10.1038/s41586-021-03819-2.").as_deref(), Some("10.1038/s41586-021-03819-2"));
+        assert_eq!(scraper.extract_doi_from_snippet("(doi: 10.1126/science.abj8754);").as_deref(), Some("10.1126/science.abj8754"));
+        // Its own parentheses are kept, the one closing the sentence isn't
+        assert_eq!(scraper.extract_doi_from_snippet("see (10.1016/S0140-6736(20)30183-5)").as_deref(), Some("10.1016/S0140-6736(20)30183-5"));
+        assert_eq!(scraper.extract_doi_from_snippet("No identifier in this snippet."), None);
+    }
+
+    #[tokio::test]
+    async fn snippet_doi_from_a_doi_org_link() {
+        let scraper = scraper().await;
+        assert_eq!(
+            scraper.extract_doi_from_snippet("Full text at https://doi.org/10.1371/journal.pone.0123456, open access").as_deref(),
+            Some("10.1371/journal.pone.0123456")
+        );
+    }
+
+    #[test]
+    fn encoded_doi_keeps_slashes_and_escapes_the_rest() {
+        assert_eq!(
+            DOIScraper::encode_doi("10.1002/(SICI)1097-4636(199706)35:4<473::AID-JBM7>3.0.CO;2-E"),
+            "10.1002/%28SICI%291097-4636%28199706%2935%3A4%3C473%3A%3AAID-JBM7%3E3.0.CO%3B2-E"
+        );
+        assert_eq!(DOIScraper::encode_doi(" 10.1000/a#b/c "), "10.1000/a%23b/c");
+    }
+
+    #[test]
+    fn jats_abstract_becomes_plain_paragraphs() {
+        let raw = "<jats:title>Abstract</jats:title>\n<jats:p>Proteins fold in &lt;1 ms when\n   the  chain is short &amp; the loop is &quot;tight&quot;.</jats:p>\
+            <jats:p>Longer chains&nbsp;take the <jats:italic>slow</jats:italic> route.</jats:p>";
+        assert_eq!(
+            DOIScraper::clean_abstract(raw),
+            "Proteins fold in <1 ms when the chain is short & the loop is \"tight\".\n\nLonger chains take the slow route."
+        );
+    }
+
+    #[test]
+    fn plain_abstract_is_only_trimmed() {
+        assert_eq!(DOIScraper::clean_abstract("  Already plain text.\n"), "Already plain text.");
+    }
+
+    #[test]
+    fn truncation_never_splits_a_character() {
+        let text = "蛋白质🧬折叠 ok é";
+        for max_len in 0..=text.len() + 2 {
+            let cut = DOIScraper::safe_truncate(text, max_len);
+            assert!(cut.len() <= max_len);
+            assert!(text.starts_with(cut));
+        }
+        // The emoji is bytes 9..13, cutting inside it drops it whole
+        assert_eq!(DOIScraper::safe_truncate(text, 11), "蛋白质");
+    }
+
+    #[test]
+    fn inverted_index_rebuilds_word_order() {
+        // "the" and "of" at several positions, the positions themselves out of order
+        let index = serde_json::json!({
+            "of": [6, 2],
+            "the": [4, 0],
+            "structure": [1],
+            "proteins": [3],
+            "shape": [5],
+            "folding": [7],
+        });
+        assert_eq!(DOIScraper::abstract_from_inverted_index(&index), "the structure of proteins the shape of folding");
+    }
+
+    #[test]
+    fn inverted_index_that_is_not_an_object_is_empty() {
+        assert_eq!(DOIScraper::abstract_from_inverted_index(&serde_json::Value::Null), "");
+        assert_eq!(DOIScraper::abstract_from_inverted_index(&serde_json::json!(["the", "shape"])), "");
+        assert_eq!(DOIScraper::abstract_from_inverted_index(&serde_json::json!({})), "");
+    }
+}
@@ -0,0 +1,150 @@
+// check-links: requests every saved URL and DOI again and reports the ones that stopped resolving.
+// A paper only counts as dead when none of its links work, a rotted landing page with a live
+// DOI can still be found.
+
+use crate::markdown;
+use crate::ratelimit::RateLimiter;
+use crate::results::{self, BOM, PaperResult};
+use anyhow::Result;
+use futures::{StreamExt, stream};
+use reqwest::{Client, StatusCode, redirect};
+use std::fs;
+use tokio::time::Duration;
+
+const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36";
+
+struct LinkProblem {
+    link: String,
+    detail: String,
+}
+
+struct Checkers {
+    // Publisher pages are followed through their redirects to the final page
+    pages: Client,
+    // doi.org answers a registered DOI with a redirect, following it would only test the publisher again
+    dois: Client,
+    limiter: RateLimiter,
+}
+
+// Returns (papers checked, papers dead)
+pub async fn check_links(path: &str, concurrency: usize, interval: Duration, annotate: bool, report: &str) -> Result<(usize, usize)> {
+    let contents = fs::read_to_string(path)?;
+    let mut papers = results::parse_results(&contents);
+
+    let checkers = Checkers {
+        pages: Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(Duration::from_secs(20))
+            .build()?,
+        dois: Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(Duration::from_secs(20))
+            .redirect(redirect::Policy::none())
+            .build()?,
+        limiter: RateLimiter::new(interval),
+    };
+
+    let mut outcomes: Vec<(usize, usize, Vec<LinkProblem>)> = stream::iter(papers.iter().enumerate())
+        .map(|(i, paper)| {
+            let checkers = &checkers;
+            async move {
+                let (links, problems) = check_paper(checkers, paper).await;
+                (i, links, problems)
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+
+    let mut lines = Vec::new();
+    let mut dead = 0;
+    outcomes.sort_by_key(|(i, _, _)| *i);
+
+    for (i, links, problems) in outcomes {
+        let paper = &mut papers[i];
+        let is_dead = links > 0 && problems.len() == links;
+        if is_dead {
+            dead += 1;
+        }
+        paper.dead = is_dead;
+
+        for problem in &problems {
+            let line = format!(
+                "{}  {}  ({})  {}",
+                if is_dead { "DEAD  " } else { "BROKEN" },
+                problem.link,
+                problem.detail,
+                paper.title
+            );
+            println!("{}", line);
+            lines.push(line);
+        }
+    }
+
+    if !report.is_empty() {
+        fs::write(report, lines.join("\n") + "\n")?;
+        println!("Report written to {}", report);
+    }
+
+    if annotate {
+        let mut annotated = String::new();
+        if contents.starts_with(BOM) {
+            annotated.push(BOM);
+        }
+        // Stays in whichever format it was written in, the next run's format check would
+        // refuse it otherwise
+        let format = results::detect_format(&contents);
+        annotated.push_str(&results::file_header(format, &markdown::heading(&contents)));
+        annotated.extend(papers.iter().map(|p| results::serialize_paper(p, format)));
+        fs::write(path, annotated)?;
+        println!("Marked dead papers in {}", path);
+    }
+
+    Ok((papers.len(), dead))
+}
+
+// Returns how many links were checked and which of them failed
+async fn check_paper(checkers: &Checkers, paper: &PaperResult) -> (usize, Vec<LinkProblem>) {
+    let mut links = 0;
+    let mut problems = Vec::new();
+
+    if !paper.url.is_empty() {
+        links += 1;
+        checkers.limiter.wait().await;
+        if let Err(detail) = probe(&checkers.pages, &paper.url).await {
+            problems.push(LinkProblem { link: paper.url.clone(), detail });
+        }
+    }
+
+    // "NA" and arXiv IDs have nothing to look up at doi.org
+    if paper.doi.starts_with("10.") {
+        let link = format!("https://doi.org/{}", crate::DOIScraper::encode_doi(&paper.doi));
+        links += 1;
+        checkers.limiter.wait().await;
+        if let Err(detail) = probe(&checkers.dois, &link).await {
+            problems.push(LinkProblem { link, detail });
+        }
+    }
+
+    (links, problems)
+}
+
+async fn probe(client: &Client, url: &str) -> Result<(), String> {
+    let response = client.head(url).send().await.map_err(|e| format!("unreachable: {}", e))?;
+
+    // Plenty of servers don't implement HEAD, ask for the page instead before calling it dead
+    let status = match response.status() {
+        StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED => client.get(url)
+            .send()
+            .await
+            .map_err(|e| format!("unreachable: {}", e))?
+            .status(),
+        status => status,
+    };
+
+    if status.is_success() || status.is_redirection() {
+        Ok(())
+    } else {
+        Err(status.to_string())
+    }
+}
@@ -0,0 +1,139 @@
+// Log levels, and the web UI's log: the last 500 lines for /logs, and every new line
+// broadcast as it's added for /logs/stream. Scrapers started from the CLI have no buffer and
+// only print. Lines above --log-level go nowhere, printed or buffered.
+
+use chrono::Local;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU8, Ordering};
+use tokio::sync::broadcast;
+
+const KEEP: usize = 500;
+
+// Most to least severe, a level lets through itself and everything above it
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    // Something went wrong with a paper or the run itself
+    Error,
+    // A source, file or hook failed, the run carries on without it
+    Warn,
+    // Progress: banners, every paper and its verdict
+    Info,
+    // The API-by-API detail --verbose used to print
+    Debug,
+}
+
+// One level for the whole process, the web server's searches share the CLI's
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+pub fn set_max_level(level: LogLevel) {
+    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn enabled(level: LogLevel) -> bool {
+    level as u8 <= MAX_LEVEL.load(Ordering::Relaxed)
+}
+
+tokio::task_local! {
+    // "[3/10]" while a paper is being processed, see for_paper
+    static PAPER: String;
+}
+
+// Runs one paper's work with every line it logs starting with `tag`, so with --concurrency
+// the lines of papers processed side by side can still be told apart
+pub async fn for_paper<F: Future>(tag: String, work: F) -> F::Output {
+    PAPER.scope(tag, work).await
+}
+
+// Printed, and added to the web UI's buffer when there is one
+pub fn write(logger: &Logger, level: LogLevel, message: &str) {
+    if !enabled(level) {
+        return;
+    }
+    let message = PAPER.try_with(|tag| tagged(tag, message)).unwrap_or_else(|_| message.to_string());
+    println!("{}", message);
+    if let Some(log) = logger {
+        log.push(level, &message);
+    }
+}
+
+// Blank lines stay blank, they only space the output out
+fn tagged(tag: &str, message: &str) -> String {
+    message.split('\n')
+        .map(|line| if line.trim().is_empty() { line.to_string() } else { format!("{} {}", tag, line) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// The level travels with the line so the UI can colour it
+#[derive(Clone, Serialize)]
+pub struct LogEntry {
+    pub level: LogLevel,
+    pub line: String,
+}
+
+pub struct LogBuffer {
+    lines: Mutex<Vec<LogEntry>>,
+    events: broadcast::Sender<LogEntry>,
+}
+
+// Handed to everything that logs, None outside the web server
+pub type Logger = Option<std::sync::Arc<LogBuffer>>;
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogBuffer {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(256);
+        Self {
+            lines: Mutex::new(Vec::new()),
+            events,
+        }
+    }
+
+    pub fn push(&self, level: LogLevel, message: &str) {
+        let entry = LogEntry { level, line: format!("[{}] {}", Local::now().format("%H:%M:%S"), message) };
+        if let Ok(mut lines) = self.lines.lock() {
+            lines.push(entry.clone());
+            if lines.len() > KEEP {
+                lines.remove(0);
+            }
+        }
+        // Nobody streaming is fine, send only fails when there are no subscribers
+        let _ = self.events.send(entry);
+    }
+
+    pub fn lines(&self) -> Vec<LogEntry> {
+        self.lines.lock().map(|lines| lines.clone()).unwrap_or_default()
+    }
+
+    // Only lines added from now on, /logs has the backlog
+    pub fn subscribe(&self) -> broadcast::Receiver<LogEntry> {
+        self.events.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_line_but_blank_ones_gets_the_tag() {
+        assert_eq!(tagged("[2/4]", "\nDOI: 10.1000/x\n  Abstract\n"), "\n[2/4] DOI: 10.1000/x\n[2/4]   Abstract\n");
+    }
+
+    #[tokio::test]
+    async fn only_lines_inside_for_paper_are_tagged() {
+        let buffer = std::sync::Arc::new(LogBuffer::new());
+        let logger: Logger = Some(buffer.clone());
+        write(&logger, LogLevel::Error, "before");
+        for_paper("[1/2]".to_string(), async { write(&logger, LogLevel::Error, "inside") }).await;
+        let lines: Vec<String> = buffer.lines().into_iter().map(|e| e.line.split_once("] ").unwrap().1.to_string()).collect();
+        assert_eq!(lines, ["before", "[1/2] inside"]);
+    }
+}
@@ -0,0 +1,211 @@
+// Markdown for --format markdown, readable as-is on GitHub or in Obsidian: a "# subject"
+// heading once at the top, then a "## title" section per paper with the DOI linked, the score
+// as a badge and the abstract quoted. Reading it back recovers what the other formats keep
+// except keywords and identifiers.
+
+use crate::DOIScraper;
+use crate::results::PaperResult;
+use chrono::NaiveDate;
+
+pub fn header(subject: &str) -> String {
+    format!("# {}\n\n", one_line(subject))
+}
+
+// The "# " line of an existing file, so rewriting it keeps the heading
+pub fn heading(contents: &str) -> String {
+    contents.lines()
+        .find(|l| !l.trim().is_empty())
+        .and_then(|l| l.strip_prefix("# "))
+        .unwrap_or("")
+        .trim()
+        .to_string()
+}
+
+pub fn looks_like_markdown(contents: &str) -> bool {
+    contents.lines()
+        .find(|l| !l.trim().is_empty())
+        .is_some_and(|l| l.starts_with("# "))
+}
+
+pub fn format_record(paper: &PaperResult) -> String {
+    let mut section = format!("## {}\n\n", one_line(&paper.title));
+    section.push_str(&format!("![score {:.2}](https://img.shields.io/badge/score-{:.2}-{})\n\n", paper.score, paper.score, badge_color(paper.score)));
+
+    section.push_str(&format!("- **DOI:** {}\n", doi_link(&paper.doi)));
+    if !paper.authors.is_empty() {
+        section.push_str(&format!("- **Authors:** {}\n", paper.authors.join("; ")));
+    }
+    if let Some(date) = paper.published {
+        section.push_str(&format!("- **Published:** {}\n", date.format("%Y-%m-%d")));
+    }
+    if let Some(count) = paper.cited_by {
+        section.push_str(&format!("- **Citations:** {}\n", count));
+    }
+    section.push_str(&format!("- **URL:** <{}>\n", paper.url));
+    if let Some(url) = &paper.oa_url {
+        section.push_str(&format!("- **Open Access:** <{}>\n", url));
+    }
+    if !paper.reason.trim().is_empty() {
+        section.push_str(&format!("- **Reason:** {}\n", one_line(&paper.reason)));
+    }
+    section.push_str(&format!("- **Saved:** {}\n\n", paper.timestamp));
+
+    for line in paper.abstract_text.trim().lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            section.push_str(">\n");
+        } else {
+            section.push_str(&format!("> {}\n", line));
+        }
+    }
+    section.push('\n');
+    section
+}
+
+// Every paper gets the file's heading as its subject
+pub fn parse(contents: &str) -> Vec<PaperResult> {
+    let subject = heading(contents);
+    let mut results = Vec::new();
+    let mut current: Option<PaperResult> = None;
+    let mut abstract_lines: Vec<&str> = Vec::new();
+
+    let mut finish = |paper: Option<PaperResult>, lines: &mut Vec<&str>| {
+        if let Some(mut paper) = paper {
+            paper.abstract_text = lines.join("\n").trim().to_string();
+            if paper.score > 0.0 {
+                results.push(paper);
+            }
+        }
+        lines.clear();
+    };
+
+    for line in contents.lines() {
+        if let Some(title) = line.strip_prefix("## ") {
+            finish(current.take(), &mut abstract_lines);
+            current = Some(PaperResult {
+                doi: "NA".to_string(),
+                title: title.trim().to_string(),
+                subject: subject.clone(),
+                ..Default::default()
+            });
+            continue;
+        }
+        let Some(paper) = current.as_mut() else {
+            continue;
+        };
+
+        if let Some(rest) = line.strip_prefix("![score ") {
+            paper.score = rest.split(']').next().and_then(|s| s.trim().parse().ok()).unwrap_or(0.0);
+        } else if let Some(quoted) = line.strip_prefix('>') {
+            abstract_lines.push(quoted.strip_prefix(' ').unwrap_or(quoted));
+        } else if let Some((key, value)) = line.strip_prefix("- **").and_then(|l| l.split_once(":** ")) {
+            let value = value.trim();
+            match key {
+                "DOI" => paper.doi = link_text(value),
+                "Authors" => paper.authors = value.split("; ").map(|a| a.trim().to_string()).filter(|a| !a.is_empty()).collect(),
+                "Published" => paper.published = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok(),
+                "Citations" => paper.cited_by = value.parse().ok(),
+                "URL" => paper.url = value.trim_start_matches('<').trim_end_matches('>').to_string(),
+                "Open Access" => paper.oa_url = Some(value.trim_start_matches('<').trim_end_matches('>').to_string()),
+                "Reason" => paper.reason = value.to_string(),
+                "Saved" => paper.timestamp = value.to_string(),
+                _ => {}
+            }
+        }
+    }
+    finish(current.take(), &mut abstract_lines);
+    results
+}
+
+// Newlines would end a heading or list item early
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn badge_color(score: f32) -> &'static str {
+    if score >= 0.8 {
+        "brightgreen"
+    } else if score >= 0.6 {
+        "yellow"
+    } else {
+        "orange"
+    }
+}
+
+// arXiv-only papers link to their abstract page, papers with no identifier at all aren't linked.
+// The target is percent-encoded, an unbalanced parenthesis in a DOI would end the link early
+fn doi_link(doi: &str) -> String {
+    let doi = doi.trim();
+    match doi.strip_prefix("arXiv:") {
+        _ if doi.is_empty() || doi == "NA" => "NA".to_string(),
+        Some(arxiv) => format!("[{}](https://arxiv.org/abs/{})", doi, arxiv),
+        None => format!("[{}](https://doi.org/{})", doi, DOIScraper::encode_doi(doi)),
+    }
+}
+
+// "[10.1000/xyz](https://doi.org/10.1000/xyz)" gives 10.1000/xyz, plain text stays as it is
+fn link_text(value: &str) -> String {
+    value.strip_prefix('[')
+        .and_then(|v| v.split_once("]("))
+        .map_or(value, |(text, _)| text)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Parentheses in the DOI and URL for the link syntax, a two-paragraph abstract and
+    // Markdown's own characters in the title and reason for the round trip
+    fn sample() -> PaperResult {
+        PaperResult {
+            doi: "10.1016/0022-2836(81)90087-5".to_string(),
+            title: "Identification of common molecular subsequences (*local* alignment)".to_string(),
+            authors: vec!["Temple F. Smith".to_string(), "Michael S. Waterman".to_string()],
+            url: "https://www.sciencedirect.com/science/article/pii/0022283681900875".to_string(),
+            score: 0.72,
+            reason: "The [Smith-Waterman] algorithm, background for sequence search.".to_string(),
+            subject: "sequence alignment".to_string(),
+            abstract_text: "A dynamic programming algorithm finds the best local alignment.\n\nIt extends Needleman-Wunsch.".to_string(),
+            cited_by: Some(12000),
+            published: NaiveDate::from_ymd_opt(1981, 3, 25),
+            timestamp: "2025-01-01 12:00:00".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn doi_with_parentheses_is_a_single_link() {
+        let section = format_record(&sample());
+        assert!(section.contains("- **DOI:** [10.1016/0022-2836(81)90087-5](https://doi.org/10.1016/0022-2836%2881%2990087-5)\n"), "{}", section);
+    }
+
+    #[test]
+    fn arxiv_and_missing_identifiers() {
+        assert_eq!(doi_link("arXiv:2101.00001"), "[arXiv:2101.00001](https://arxiv.org/abs/2101.00001)");
+        assert_eq!(doi_link("NA"), "NA");
+        assert_eq!(doi_link(""), "NA");
+    }
+
+    #[test]
+    fn round_trips_through_parse() {
+        let paper = sample();
+        let file = header(&paper.subject) + &format_record(&paper);
+        assert!(looks_like_markdown(&file));
+
+        let parsed = parse(&file);
+        assert_eq!(parsed.len(), 1);
+        let back = &parsed[0];
+        assert_eq!(back.doi, paper.doi);
+        assert_eq!(back.title, paper.title);
+        assert_eq!(back.authors, paper.authors);
+        assert_eq!(back.url, paper.url);
+        assert_eq!(back.score, paper.score);
+        assert_eq!(back.reason, paper.reason);
+        assert_eq!(back.subject, paper.subject);
+        assert_eq!(back.abstract_text, paper.abstract_text);
+        assert_eq!(back.cited_by, paper.cited_by);
+        assert_eq!(back.published, paper.published);
+        assert_eq!(back.timestamp, paper.timestamp);
+    }
+}
@@ -0,0 +1,143 @@
+// Pushes saved papers into a Notion database as rows.
+// The database schema is read once up front so each value can be coerced to whatever
+// property type the user set up (a DOI column may be text, a URL column may be plain text, etc).
+
+use crate::ScientificPaper;
+use crate::ratelimit::RateLimiter;
+use anyhow::{Result, anyhow};
+use reqwest::{Client, StatusCode};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use tokio::time::{sleep, Duration};
+
+const NOTION_API: &str = "https://api.notion.com/v1";
+const NOTION_VERSION: &str = "2022-06-28";
+// Notion rejects rich text chunks longer than this
+const MAX_TEXT_LEN: usize = 2000;
+
+pub struct NotionExporter {
+    client: Client,
+    token: String,
+    database_id: String,
+    // property name -> Notion property type
+    schema: HashMap<String, String>,
+    title_property: String,
+    // Notion allows an average of 3 requests per second
+    limiter: RateLimiter,
+}
+
+impl NotionExporter {
+    pub async fn connect(client: Client, token: &str, database_id: &str) -> Result<Self> {
+        let response = client
+            .get(format!("{}/databases/{}", NOTION_API, database_id))
+            .bearer_auth(token)
+            .header("Notion-Version", NOTION_VERSION)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Notion database lookup failed: {} - {}", status, body));
+        }
+
+        let data: Value = response.json().await?;
+        let mut schema = HashMap::new();
+        if let Some(properties) = data["properties"].as_object() {
+            for (name, property) in properties {
+                if let Some(kind) = property["type"].as_str() {
+                    schema.insert(name.clone(), kind.to_string());
+                }
+            }
+        }
+
+        // Every database has exactly one title column, but it's usually called "Name"
+        let title_property = schema.iter()
+            .find(|(_, kind)| kind.as_str() == "title")
+            .map(|(name, _)| name.clone())
+            .ok_or_else(|| anyhow!("Notion database has no title property"))?;
+
+        Ok(Self {
+            client,
+            token: token.to_string(),
+            database_id: database_id.to_string(),
+            schema,
+            title_property,
+            limiter: RateLimiter::new(Duration::from_millis(350)),
+        })
+    }
+
+    // Creates one row for the paper and returns the new page ID
+    pub async fn export(&self, paper: &ScientificPaper) -> Result<String> {
+        let score = format!("{:.2}", paper.relevance_score);
+        let fields = [
+            (self.title_property.as_str(), Some(paper.title.as_str())),
+            ("DOI", paper.doi.as_deref()),
+            ("URL", Some(paper.url.as_str())),
+            ("Score", Some(score.as_str())),
+            ("Abstract", Some(paper.abstract_text.as_str())),
+        ];
+
+        let mut properties = serde_json::Map::new();
+        for (name, value) in fields {
+            let (Some(kind), Some(value)) = (self.schema.get(name), value) else {
+                continue;
+            };
+            properties.insert(name.to_string(), coerce_property(kind, value)?);
+        }
+
+        let body = json!({
+            "parent": { "database_id": self.database_id },
+            "properties": properties,
+        });
+
+        for attempt in 0..2 {
+            self.limiter.wait().await;
+            let response = self.client
+                .post(format!("{}/pages", NOTION_API))
+                .bearer_auth(&self.token)
+                .header("Notion-Version", NOTION_VERSION)
+                .json(&body)
+                .send()
+                .await?;
+
+            if response.status() == StatusCode::TOO_MANY_REQUESTS && attempt == 0 {
+                let wait = response.headers()
+                    .get("Retry-After")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(1);
+                sleep(Duration::from_secs(wait)).await;
+                continue;
+            }
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                return Err(anyhow!("Notion returned {}: {}", status, body));
+            }
+
+            let page: Value = response.json().await?;
+            return page["id"].as_str()
+                .map(|id| id.to_string())
+                .ok_or_else(|| anyhow!("Notion response had no page id"));
+        }
+
+        Err(anyhow!("Notion rate limit persisted after retry"))
+    }
+}
+
+fn coerce_property(kind: &str, value: &str) -> Result<Value> {
+    let text = || json!([{ "text": { "content": crate::DOIScraper::safe_truncate(value, MAX_TEXT_LEN) } }]);
+
+    match kind {
+        "title" => Ok(json!({ "title": text() })),
+        "rich_text" => Ok(json!({ "rich_text": text() })),
+        "url" => Ok(json!({ "url": value })),
+        "select" => Ok(json!({ "select": { "name": value } })),
+        "number" => value.parse::<f64>()
+            .map(|n| json!({ "number": n }))
+            .map_err(|_| anyhow!("'{}' is not a number", value)),
+        other => Err(anyhow!("unsupported Notion property type '{}'", other)),
+    }
+}
@@ -0,0 +1,87 @@
+// --download-pdf: saves the free PDF of every accepted paper into --pdf-dir, named after its
+// DOI. Only sources known to be free are tried, the Unpaywall copy or arXiv.
+
+use crate::results::PaperResult;
+use anyhow::{Result, anyhow};
+use reqwest::Client;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::time::Duration;
+
+pub enum Download {
+    Saved(u64),
+    // Landing pages and login walls come back as HTML, the content type is kept for the log
+    NotPdf(String),
+    AlreadyThere,
+}
+
+pub fn pdf_url(paper: &PaperResult) -> Option<String> {
+    paper.oa_url.clone()
+        .or_else(|| paper.identifiers.get("arxiv").map(|id| format!("https://arxiv.org/pdf/{}", id)))
+        .or_else(|| paper.doi.strip_prefix("arXiv:").map(|id| format!("https://arxiv.org/pdf/{}", id)))
+}
+
+// "10.1000/abc.123" becomes "10.1000_abc.123.pdf". Anything else a filesystem might object to
+// goes the same way as the slash
+pub fn file_name(paper: &PaperResult) -> Option<String> {
+    let id = match paper.doi.trim() {
+        "" | "NA" => format!("arXiv:{}", paper.identifiers.get("arxiv")?),
+        doi => doi.to_string(),
+    };
+    let safe: String = id.chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '.' | '-' | '(' | ')') { c } else { '_' })
+        .collect();
+    Some(format!("{}.pdf", safe))
+}
+
+// Streams the body to a .part file next to the destination and renames it once complete, so
+// an interrupted download never looks like a finished PDF
+pub async fn download(client: &Client, url: &str, dir: &str, name: &str) -> Result<Download> {
+    let dest = Path::new(dir).join(name);
+    if fs::try_exists(&dest).await.unwrap_or(false) {
+        return Ok(Download::AlreadyThere);
+    }
+
+    let mut response = client.get(url)
+        .header("Accept", "application/pdf")
+        .timeout(Duration::from_secs(60))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("{} returned {}", url, response.status()));
+    }
+    let content_type = response.headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+    if !content_type.to_lowercase().starts_with("application/pdf") {
+        return Ok(Download::NotPdf(if content_type.is_empty() { "no content type".to_string() } else { content_type }));
+    }
+
+    fs::create_dir_all(dir).await?;
+    let partial = PathBuf::from(format!("{}.part", dest.display()));
+    let written = write_body(&mut response, &partial).await;
+    match written {
+        Ok(bytes) => {
+            fs::rename(&partial, &dest).await?;
+            Ok(Download::Saved(bytes))
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&partial).await;
+            Err(e)
+        }
+    }
+}
+
+async fn write_body(response: &mut reqwest::Response, path: &Path) -> Result<u64> {
+    let mut file = fs::File::create(path).await?;
+    let mut bytes = 0;
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk).await?;
+        bytes += chunk.len() as u64;
+    }
+    file.flush().await?;
+    Ok(bytes)
+}
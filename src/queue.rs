@@ -0,0 +1,76 @@
+// Append-only JSON-lines work queue so a crashed run can pick up where it died.
+// Every result is written as an `add` when discovered and gets a `done` once processed,
+// replaying the file gives back whatever never finished.
+
+use crate::SearchResult;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum Entry {
+    Add { item: SearchResult },
+    Done { url: String },
+}
+
+pub struct WorkQueue {
+    path: String,
+}
+
+impl WorkQueue {
+    pub fn new(path: &str) -> Self {
+        Self { path: path.to_string() }
+    }
+
+    pub fn reset(&self) -> Result<()> {
+        fs::write(&self.path, "")?;
+        Ok(())
+    }
+
+    // Called after a clean finish, nothing left worth resuming
+    pub fn remove(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+
+    pub fn enqueue(&self, item: &SearchResult) -> Result<()> {
+        self.append(&Entry::Add { item: item.clone() })
+    }
+
+    pub fn mark_done(&self, url: &str) -> Result<()> {
+        self.append(&Entry::Done { url: url.to_string() })
+    }
+
+    pub fn pending(&self) -> Result<Vec<SearchResult>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        let mut added = Vec::new();
+        let mut done = HashSet::new();
+        // A crash mid-write can leave a torn last line, which is simply skipped
+        for entry in contents.lines().filter_map(|l| serde_json::from_str::<Entry>(l).ok()) {
+            match entry {
+                Entry::Add { item } => added.push(item),
+                Entry::Done { url } => {
+                    done.insert(url);
+                }
+            }
+        }
+
+        added.retain(|item: &SearchResult| !done.contains(&item.url));
+        Ok(added)
+    }
+
+    fn append(&self, entry: &Entry) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+}
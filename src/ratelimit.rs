@@ -0,0 +1,62 @@
+// Spaces out outbound API requests. One limiter can be shared by every scraper in the
+// process (the web server hands the same one to each search) so they're polite together.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::time::{sleep_until, Duration, Instant};
+
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    // Reserves the next free slot and sleeps until it comes around
+    pub async fn wait(&self) {
+        let slot = {
+            let mut next = self.next_slot.lock().unwrap();
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        sleep_until(slot).await;
+    }
+}
+
+// The same spacing kept separately for every host, so papers on different publishers' sites
+// are fetched side by side while any one site still sees a polite gap between requests
+pub struct DomainRateLimiter {
+    interval: Duration,
+    next_slots: Mutex<HashMap<String, Instant>>,
+}
+
+impl DomainRateLimiter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // URLs without a host all share one slot
+    pub async fn wait(&self, url: &str) {
+        let host = reqwest::Url::parse(url).ok()
+            .and_then(|u| u.host_str().map(|h| h.to_lowercase()))
+            .unwrap_or_default();
+        let slot = {
+            let mut slots = self.next_slots.lock().unwrap();
+            let next = slots.entry(host).or_insert_with(Instant::now);
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        sleep_until(slot).await;
+    }
+}
@@ -0,0 +1,770 @@
+// The results.txt block format, shared by the scraper (writing), the web UI and the
+// file utilities (reading).
+
+use crate::OutputFormat;
+use crate::{jsonl, markdown, ris, spreadsheet};
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PaperResult {
+    pub doi: String,
+    pub title: String,
+    // "Given Family", in the order the source lists them
+    pub authors: Vec<String>,
+    pub url: String,
+    pub score: f32,
+    pub reason: String,
+    // The --subject entry it was found for, empty in files from before subjects were recorded
+    pub subject: String,
+    pub abstract_text: String,
+    pub keywords: Vec<String>,
+    pub cited_by: Option<u32>,
+    pub published: Option<NaiveDate>,
+    // Source metadata's resource type ("journal-article", "Dataset", ...), empty when unknown
+    pub resource_type: String,
+    // SearXNG engine that returned the result
+    pub engine: String,
+    // Every identifier found for the paper, keyed "doi", "pmid", "pmcid" or "arxiv"
+    pub identifiers: HashMap<String, String>,
+    // Free copy from Unpaywall (--unpaywall-email)
+    pub oa_url: Option<String>,
+    pub timestamp: String,
+    // Set by check-links when neither the URL nor the DOI resolves anymore
+    pub dead: bool,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum SortBy {
+    // Leave papers in the order they were saved
+    None,
+    // Highest relevance first, citation count breaks ties
+    Score,
+    // Most cited first, relevance breaks ties
+    Citations,
+    // Newest publication first, undated papers last
+    Date,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum MergeStrategy {
+    // Keep the entry with the highest relevance score
+    Score,
+    // Keep the most recently saved entry
+    Recent,
+}
+
+// Written only with --output-bom, readers skip it either way
+pub const BOM: char = '\u{FEFF}';
+
+pub fn format_block(paper: &PaperResult) -> String {
+    let separator = "=".repeat(70);
+    let mut block = String::new();
+
+    block.push_str(&format!("\n{}\n", separator));
+    block.push_str(&format!("DOI: {}\n", paper.doi));
+    block.push_str(&format!("Title: {}\n", paper.title));
+    if !paper.authors.is_empty() {
+        block.push_str(&format!("Authors: {}\n", paper.authors.join("; ")));
+    }
+    block.push_str(&format!("URL: {}\n", paper.url));
+    block.push_str(&format!("Score: {:.2}\n", paper.score));
+    // Kept on one line so a stray "DOI:" or "====" in model output can't split the block
+    block.push_str(&format!("Reason: {}\n", paper.reason.split_whitespace().collect::<Vec<_>>().join(" ")));
+    if !paper.subject.is_empty() {
+        block.push_str(&format!("Subject: {}\n", paper.subject));
+    }
+    if !paper.keywords.is_empty() {
+        block.push_str(&format!("Keywords: {}\n", paper.keywords.join("; ")));
+    }
+    if let Some(count) = paper.cited_by {
+        block.push_str(&format!("Citations: {}\n", count));
+    }
+    if let Some(date) = paper.published {
+        block.push_str(&format!("Published: {}\n", date.format("%Y-%m-%d")));
+    }
+    if !paper.resource_type.is_empty() {
+        block.push_str(&format!("Type: {}\n", paper.resource_type));
+    }
+    if !paper.engine.is_empty() {
+        block.push_str(&format!("Engine: {}\n", paper.engine));
+    }
+    if !paper.identifiers.is_empty() {
+        block.push_str(&format!("IDs: {}\n", format_identifiers(&paper.identifiers)));
+    }
+    if let Some(url) = &paper.oa_url {
+        block.push_str(&format!("Open Access: {}\n", url));
+    }
+    block.push_str(&format!("Saved: {}\n", paper.timestamp));
+    if paper.dead {
+        block.push_str("Status: DEAD\n");
+    }
+    block.push_str(&format!("Abstract:\n{}\n", paper.abstract_text));
+    block.push_str(&format!("{}\n\n", separator));
+
+    block
+}
+
+// What a saved paper looks like on disk in each --format
+pub fn serialize_paper(paper: &PaperResult, format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Text => format_block(paper),
+        OutputFormat::Ris => ris::format_record(paper),
+        OutputFormat::Jsonl => jsonl::format_record(paper),
+        OutputFormat::Csv => spreadsheet::format_record(paper),
+        OutputFormat::Markdown => markdown::format_record(paper),
+    }
+}
+
+// Written once at the top of a new file: CSV's column names, markdown's "# subject" heading
+pub fn file_header(format: OutputFormat, subject: &str) -> String {
+    match format {
+        OutputFormat::Csv => spreadsheet::HEADER.to_string(),
+        OutputFormat::Markdown => markdown::header(subject),
+        _ => String::new(),
+    }
+}
+
+// Which --format a results file was written in, blocks when it's empty
+pub fn detect_format(contents: &str) -> OutputFormat {
+    let contents = contents.strip_prefix(BOM).unwrap_or(contents);
+    if ris::looks_like_ris(contents) {
+        OutputFormat::Ris
+    } else if jsonl::looks_like_jsonl(contents) {
+        OutputFormat::Jsonl
+    } else if spreadsheet::looks_like_csv(contents) {
+        OutputFormat::Csv
+    } else if markdown::looks_like_markdown(contents) {
+        OutputFormat::Markdown
+    } else {
+        OutputFormat::Text
+    }
+}
+
+pub fn read_results(filepath: &str) -> Vec<PaperResult> {
+    fs::read_to_string(filepath)
+        .map(|contents| parse_results(&contents))
+        .unwrap_or_default()
+}
+
+pub fn parse_results(contents: &str) -> Vec<PaperResult> {
+    let contents = contents.strip_prefix(BOM).unwrap_or(contents);
+    match detect_format(contents) {
+        OutputFormat::Ris => return ris::parse(contents),
+        OutputFormat::Jsonl => return jsonl::parse(contents),
+        OutputFormat::Csv => return spreadsheet::parse(contents),
+        OutputFormat::Markdown => return markdown::parse(contents),
+        OutputFormat::Text => {}
+    }
+    parse_blocks(contents.lines(), |_| true, usize::MAX).0
+}
+
+// The first `limit` papers `keep` accepts, in file order, and whether the file has more.
+// Block files are read a line at a time and left as soon as one more match turns up, so the
+// web UI's first page doesn't wait on the whole file. The other formats are read whole
+pub fn read_results_until(filepath: &str, keep: impl Fn(&PaperResult) -> bool, limit: usize) -> (Vec<PaperResult>, bool) {
+    let Ok(file) = fs::File::open(filepath) else {
+        return (Vec::new(), false);
+    };
+    let mut lines = BufReader::new(file).lines().map_while(|line| line.ok());
+    let head = read_head(&mut lines);
+
+    if detect_format(&head.join("\n")) == OutputFormat::Text {
+        return parse_blocks(head.into_iter().chain(lines), keep, limit);
+    }
+    let mut contents = head.join("\n");
+    for line in lines {
+        contents.push('\n');
+        contents.push_str(&line);
+    }
+    let mut results: Vec<PaperResult> = parse_results(&contents).into_iter().filter(|p| keep(p)).collect();
+    let more = results.len() > limit;
+    results.truncate(limit);
+    (results, more)
+}
+
+// read_results_until from the other end: the last `limit` papers `keep` accepts, newest
+// first. Block files are read backwards a chunk at a time, the other formats whole
+pub fn read_newest_until(filepath: &str, keep: impl Fn(&PaperResult) -> bool, limit: usize) -> (Vec<PaperResult>, bool) {
+    let Ok(file) = fs::File::open(filepath) else {
+        return (Vec::new(), false);
+    };
+    let head = read_head(&mut BufReader::new(&file).lines().map_while(|line| line.ok()));
+    if detect_format(&head.join("\n")) != OutputFormat::Text {
+        let mut results: Vec<PaperResult> = read_results(filepath).into_iter().filter(|p| keep(p)).collect();
+        results.reverse();
+        let more = results.len() > limit;
+        results.truncate(limit);
+        return (results, more);
+    }
+    read_blocks_backwards(file, keep, limit).unwrap_or_default()
+}
+
+// The first line with anything on it is enough to tell the formats apart
+fn read_head(lines: &mut impl Iterator<Item = String>) -> Vec<String> {
+    let mut head = Vec::new();
+    for line in lines {
+        let line = if head.is_empty() { line.strip_prefix(BOM).map(str::to_string).unwrap_or(line) } else { line };
+        let blank = line.trim().is_empty();
+        head.push(line);
+        if !blank {
+            break;
+        }
+    }
+    head
+}
+
+// Every block starts with a "\n====" separator, so whatever follows the last one in the bytes
+// read so far is a whole block. The separator is ASCII and can't split a character
+fn read_blocks_backwards(mut file: fs::File, keep: impl Fn(&PaperResult) -> bool, limit: usize) -> std::io::Result<(Vec<PaperResult>, bool)> {
+    const CHUNK: u64 = 64 * 1024;
+    let mut results = Vec::new();
+    let mut pos = file.seek(SeekFrom::End(0))?;
+    let mut tail: Vec<u8> = Vec::new();
+
+    loop {
+        while let Some(start) = tail.windows(5).rposition(|w| w == b"\n====") {
+            let block = tail.split_off(start + 1);
+            let (paper, _) = parse_blocks(String::from_utf8_lossy(&block).lines(), &keep, 1);
+            if let Some(paper) = paper.into_iter().next() {
+                if results.len() == limit {
+                    return Ok((results, true));
+                }
+                results.push(paper);
+            }
+        }
+        if pos == 0 {
+            break;
+        }
+        let start = pos.saturating_sub(CHUNK);
+        let mut chunk = vec![0; (pos - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        pos = start;
+    }
+
+    // A first block written without the leading newline
+    for paper in parse_blocks(String::from_utf8_lossy(&tail).lines(), &keep, usize::MAX).0.into_iter().rev() {
+        if results.len() == limit {
+            return Ok((results, true));
+        }
+        results.push(paper);
+    }
+    Ok((results, false))
+}
+
+fn parse_blocks<S: AsRef<str>>(lines: impl IntoIterator<Item = S>, keep: impl Fn(&PaperResult) -> bool, limit: usize) -> (Vec<PaperResult>, bool) {
+    let mut results = Vec::new();
+    let mut current_paper: Option<PaperResult> = None;
+    let mut abstract_lines = Vec::new();
+    let mut in_abstract = false;
+
+    for line in lines {
+        let line = line.as_ref();
+        if line.starts_with("====") {
+            if let Some(paper) = finish_block(current_paper.take(), &mut abstract_lines)
+                && keep(&paper)
+            {
+                if results.len() == limit {
+                    return (results, true);
+                }
+                results.push(paper);
+            }
+            in_abstract = false;
+            current_paper = Some(PaperResult::default());
+        } else if line.starts_with("DOI: ") {
+            if let Some(ref mut paper) = current_paper {
+                paper.doi = line.trim_start_matches("DOI: ").to_string();
+            }
+            in_abstract = false;
+        } else if line.starts_with("Title: ") {
+            if let Some(ref mut paper) = current_paper {
+                paper.title = line.trim_start_matches("Title: ").to_string();
+            }
+            in_abstract = false;
+        } else if line.starts_with("Authors: ") {
+            if let Some(ref mut paper) = current_paper {
+                paper.authors = line.trim_start_matches("Authors: ")
+                    .split("; ")
+                    .map(|a| a.trim().to_string())
+                    .filter(|a| !a.is_empty())
+                    .collect();
+            }
+            in_abstract = false;
+        } else if line.starts_with("URL: ") {
+            if let Some(ref mut paper) = current_paper {
+                paper.url = line.trim_start_matches("URL: ").to_string();
+            }
+            in_abstract = false;
+        } else if line.starts_with("Score: ") {
+            if let Some(ref mut paper) = current_paper
+                && let Ok(score) = line.trim_start_matches("Score: ").parse::<f32>()
+            {
+                paper.score = score;
+            }
+            in_abstract = false;
+        } else if line.starts_with("Reason: ") {
+            if let Some(ref mut paper) = current_paper {
+                paper.reason = line.trim_start_matches("Reason: ").to_string();
+            }
+            in_abstract = false;
+        } else if line.starts_with("Subject: ") {
+            if let Some(ref mut paper) = current_paper {
+                paper.subject = line.trim_start_matches("Subject: ").to_string();
+            }
+            in_abstract = false;
+        } else if line.starts_with("Keywords: ") {
+            if let Some(ref mut paper) = current_paper {
+                paper.keywords = line.trim_start_matches("Keywords: ")
+                    .split("; ")
+                    .map(|k| k.trim().to_string())
+                    .filter(|k| !k.is_empty())
+                    .collect();
+            }
+            in_abstract = false;
+        } else if line.starts_with("Citations: ") {
+            if let Some(ref mut paper) = current_paper {
+                paper.cited_by = line.trim_start_matches("Citations: ").parse().ok();
+            }
+            in_abstract = false;
+        } else if line.starts_with("Published: ") {
+            if let Some(ref mut paper) = current_paper {
+                paper.published = NaiveDate::parse_from_str(line.trim_start_matches("Published: "), "%Y-%m-%d").ok();
+            }
+            in_abstract = false;
+        } else if line.starts_with("Type: ") {
+            if let Some(ref mut paper) = current_paper {
+                paper.resource_type = line.trim_start_matches("Type: ").to_string();
+            }
+            in_abstract = false;
+        } else if line.starts_with("Engine: ") {
+            if let Some(ref mut paper) = current_paper {
+                paper.engine = line.trim_start_matches("Engine: ").to_string();
+            }
+            in_abstract = false;
+        } else if line.starts_with("IDs: ") {
+            if let Some(ref mut paper) = current_paper {
+                paper.identifiers = parse_identifiers(line.trim_start_matches("IDs: "));
+            }
+            in_abstract = false;
+        } else if line.starts_with("Open Access: ") {
+            if let Some(ref mut paper) = current_paper {
+                paper.oa_url = Some(line.trim_start_matches("Open Access: ").to_string());
+            }
+            in_abstract = false;
+        } else if line.starts_with("Saved: ") {
+            if let Some(ref mut paper) = current_paper {
+                paper.timestamp = line.trim_start_matches("Saved: ").to_string();
+            }
+            in_abstract = false;
+        } else if line.starts_with("Status: ") {
+            if let Some(ref mut paper) = current_paper {
+                paper.dead = line.trim_start_matches("Status: ") == "DEAD";
+            }
+            in_abstract = false;
+        } else if line.starts_with("Abstract:") {
+            in_abstract = true;
+            abstract_lines.clear();
+        } else if in_abstract && !line.trim().is_empty() {
+            abstract_lines.push(line.trim().to_string());
+        }
+    }
+
+    if let Some(paper) = finish_block(current_paper, &mut abstract_lines)
+        && keep(&paper)
+    {
+        if results.len() == limit {
+            return (results, true);
+        }
+        results.push(paper);
+    }
+
+    (results, false)
+}
+
+fn finish_block(paper: Option<PaperResult>, abstract_lines: &mut Vec<String>) -> Option<PaperResult> {
+    let mut paper = paper?;
+    if !abstract_lines.is_empty() {
+        paper.abstract_text = abstract_lines.join(" ").trim().to_string();
+        abstract_lines.clear();
+    }
+    // Only add papers with score > 0.0, redundant safeguard,
+    (paper.score > 0.0).then_some(paper)
+}
+
+// "arxiv=2101.00001; doi=10.1000/xyz; pmid=123", sorted so the same paper always writes the same line
+pub fn format_identifiers(identifiers: &HashMap<String, String>) -> String {
+    let mut pairs: Vec<_> = identifiers.iter().collect();
+    pairs.sort();
+    pairs.iter()
+        .map(|(kind, id)| format!("{}={}", kind, id))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn parse_identifiers(line: &str) -> HashMap<String, String> {
+    line.split("; ")
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(kind, id)| (kind.trim().to_string(), id.trim().to_string()))
+        .filter(|(kind, id)| !kind.is_empty() && !id.is_empty())
+        .collect()
+}
+
+// Ties fall back to DOI then title, so the same papers always come out in the same order
+// no matter which order they were saved in
+pub fn sort_results(results: &mut [PaperResult], sort_by: SortBy) {
+    let citations = |p: &PaperResult| p.cited_by.unwrap_or(0);
+    let tiebreak = |a: &PaperResult, b: &PaperResult| a.doi.cmp(&b.doi).then_with(|| a.title.cmp(&b.title));
+    match sort_by {
+        SortBy::None => {}
+        SortBy::Score => results.sort_by(|a, b| {
+            b.score.total_cmp(&a.score)
+                .then_with(|| citations(b).cmp(&citations(a)))
+                .then_with(|| tiebreak(a, b))
+        }),
+        SortBy::Citations => results.sort_by(|a, b| {
+            citations(b).cmp(&citations(a))
+                .then_with(|| b.score.total_cmp(&a.score))
+                .then_with(|| tiebreak(a, b))
+        }),
+        // None < Some, so comparing b to a puts undated papers at the end
+        SortBy::Date => results.sort_by(|a, b| {
+            b.published.cmp(&a.published)
+                .then_with(|| b.score.total_cmp(&a.score))
+                .then_with(|| tiebreak(a, b))
+        }),
+    }
+}
+
+pub fn sort_file(path: &str, sort_by: SortBy) -> Result<()> {
+    let contents = fs::read_to_string(path)?;
+    let mut results = parse_results(&contents);
+    sort_results(&mut results, sort_by);
+
+    let mut sorted = String::new();
+    if contents.starts_with(BOM) {
+        sorted.push(BOM);
+    }
+    // Stays in whichever format it was written in
+    let format = detect_format(&contents);
+    sorted.push_str(&file_header(format, &markdown::heading(&contents)));
+    sorted.extend(results.iter().map(|p| serialize_paper(p, format)));
+    fs::write(path, sorted)?;
+    Ok(())
+}
+
+// Rewrites the file without the papers saved under `doi`, in whichever format it was written
+// in. Returns how many were removed and how many are left
+pub fn remove_doi(path: &str, doi: &str) -> Result<(usize, usize)> {
+    let contents = fs::read_to_string(path)?;
+    let (removed, kept): (Vec<PaperResult>, Vec<PaperResult>) = parse_results(&contents)
+        .into_iter()
+        .partition(|p| p.doi.eq_ignore_ascii_case(doi.trim()));
+    if removed.is_empty() {
+        return Ok((0, kept.len()));
+    }
+
+    let mut rewritten = String::new();
+    if contents.starts_with(BOM) {
+        rewritten.push(BOM);
+    }
+    let format = detect_format(&contents);
+    rewritten.push_str(&file_header(format, &markdown::heading(&contents)));
+    rewritten.extend(kept.iter().map(|p| serialize_paper(p, format)));
+    fs::write(path, rewritten)?;
+    Ok((removed.len(), kept.len()))
+}
+
+// Lowercased, punctuation stripped, whitespace collapsed
+pub fn normalize_title(title: &str) -> String {
+    title.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Same paper across files: matching DOI, or matching title when there's no DOI
+fn dedup_key(paper: &PaperResult) -> String {
+    let doi = paper.doi.trim().to_lowercase();
+    if !doi.is_empty() && doi != "na" {
+        format!("doi:{}", doi)
+    } else {
+        format!("title:{}", normalize_title(&paper.title))
+    }
+}
+
+// Every field of every paper as a JSON array. A paper saved more than once (across runs, or
+// re-scored) shows up once, in its first position but with its latest data
+pub fn export_full(input: &str, path: &str) -> Result<usize> {
+    export_papers(parse_results(&fs::read_to_string(input)?), path)
+}
+
+pub fn export_papers(saved: Vec<PaperResult>, path: &str) -> Result<usize> {
+    let mut papers: Vec<PaperResult> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for paper in saved {
+        match index.get(&dedup_key(&paper)) {
+            Some(&i) => papers[i] = paper,
+            None => {
+                index.insert(dedup_key(&paper), papers.len());
+                papers.push(paper);
+            }
+        }
+    }
+
+    fs::write(path, serde_json::to_string_pretty(&papers)?)?;
+    Ok(papers.len())
+}
+
+// Keeps the papers scoring at least min_score, in their original order. Returns every paper
+// read, so the caller can show the score distribution the threshold cut through
+pub fn filter_file(input: &str, output: &str, min_score: f32) -> Result<(Vec<PaperResult>, usize)> {
+    let contents = fs::read_to_string(input)?;
+    let papers = parse_results(&contents);
+
+    let mut filtered = String::new();
+    if contents.starts_with(BOM) {
+        filtered.push(BOM);
+    }
+    let kept: Vec<&PaperResult> = papers.iter().filter(|p| p.score >= min_score).collect();
+    let format = detect_format(&contents);
+    filtered.push_str(&file_header(format, &markdown::heading(&contents)));
+    filtered.extend(kept.iter().map(|p| serialize_paper(p, format)));
+    fs::write(output, filtered)?;
+
+    let kept = kept.len();
+    Ok((papers, kept))
+}
+
+// One line per 0.1 wide score bucket, highest first, with a bar and a mark on the buckets
+// at or above the threshold
+pub fn score_distribution(papers: &[PaperResult], min_score: f32) -> Vec<String> {
+    let mut buckets = [0usize; 10];
+    for paper in papers {
+        let bucket = ((paper.score.clamp(0.0, 1.0) * 10.0) as usize).min(9);
+        buckets[bucket] += 1;
+    }
+
+    let widest = buckets.iter().copied().max().unwrap_or(0).max(1);
+    (0..10).rev()
+        .map(|i| {
+            let low = i as f32 / 10.0;
+            let bar = "#".repeat((buckets[i] * 40).div_ceil(widest));
+            let marker = if low >= min_score { "+" } else { " " };
+            format!("{} {:.1}-{:.1} {:>5}  {}", marker, low, low + 0.1, buckets[i], bar)
+        })
+        .collect()
+}
+
+// Written in the first input's format, like filter_file. Returns (papers read, papers written)
+pub fn merge_files(inputs: &[String], output: &str, strategy: MergeStrategy) -> Result<(usize, usize)> {
+    let mut merged: Vec<PaperResult> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut read = 0;
+    let mut first: Option<String> = None;
+
+    for input in inputs {
+        let contents = fs::read_to_string(input)?;
+        for paper in parse_results(&contents) {
+            read += 1;
+            let key = dedup_key(&paper);
+
+            match index.get(&key) {
+                Some(&i) => {
+                    let replace = match strategy {
+                        MergeStrategy::Score => paper.score > merged[i].score,
+                        // Timestamps are "%Y-%m-%d %H:%M:%S" so they compare as strings
+                        MergeStrategy::Recent => paper.timestamp > merged[i].timestamp,
+                    };
+                    if replace {
+                        merged[i] = paper;
+                    }
+                }
+                None => {
+                    index.insert(key, merged.len());
+                    merged.push(paper);
+                }
+            }
+        }
+        first.get_or_insert(contents);
+    }
+
+    let first = first.unwrap_or_default();
+    let format = detect_format(&first);
+    let mut contents = String::new();
+    if first.starts_with(BOM) {
+        contents.push(BOM);
+    }
+    contents.push_str(&file_header(format, &markdown::heading(&first)));
+    contents.extend(merged.iter().map(|p| serialize_paper(p, format)));
+    fs::write(output, contents)?;
+
+    Ok((read, merged.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paper(doi: &str, title: &str, score: f32, cited_by: Option<u32>, year: Option<i32>) -> PaperResult {
+        PaperResult {
+            doi: doi.to_string(),
+            title: title.to_string(),
+            url: format!("https://example.org/{}", title.to_lowercase().replace(' ', "-")),
+            score,
+            reason: "Relevant.".to_string(),
+            cited_by,
+            published: year.and_then(|y| NaiveDate::from_ymd_opt(y, 6, 1)),
+            timestamp: "2025-01-01 12:00:00".to_string(),
+            abstract_text: format!("About {}.", title),
+            ..Default::default()
+        }
+    }
+
+    // Ties on score, citations and date, and papers without a DOI
+    fn corpus() -> Vec<PaperResult> {
+        vec![
+            paper("10.1000/c", "Gamma", 0.80, Some(5), Some(2020)),
+            paper("NA", "Beta", 0.80, Some(5), Some(2020)),
+            paper("10.1000/a", "Alpha", 0.90, None, None),
+            paper("NA", "Alpha", 0.80, Some(5), Some(2020)),
+            paper("10.1000/b", "Delta", 0.70, Some(12), Some(2019)),
+            paper("10.1000/d", "Epsilon", 0.80, Some(5), Some(2021)),
+            paper("10.1000/e", "Zeta", 0.90, None, None),
+        ]
+    }
+
+    fn sorted_text(mut papers: Vec<PaperResult>, sort_by: SortBy) -> String {
+        sort_results(&mut papers, sort_by);
+        papers.iter().map(format_block).collect()
+    }
+
+    #[test]
+    fn sorting_is_byte_identical_whatever_the_saved_order() {
+        for sort_by in [SortBy::Score, SortBy::Citations, SortBy::Date] {
+            let mut reversed = corpus();
+            reversed.reverse();
+            let mut rotated = corpus();
+            rotated.rotate_left(3);
+
+            let expected = sorted_text(corpus(), sort_by);
+            assert_eq!(sorted_text(reversed, sort_by), expected, "{:?}", sort_by);
+            assert_eq!(sorted_text(rotated, sort_by), expected, "{:?}", sort_by);
+        }
+    }
+
+    #[test]
+    fn score_ties_fall_back_to_doi_then_title() {
+        let mut papers = corpus();
+        sort_results(&mut papers, SortBy::Score);
+        let order: Vec<(&str, &str)> = papers.iter().map(|p| (p.doi.as_str(), p.title.as_str())).collect();
+        assert_eq!(order, [
+            ("10.1000/a", "Alpha"),
+            ("10.1000/e", "Zeta"),
+            ("10.1000/c", "Gamma"),
+            ("10.1000/d", "Epsilon"),
+            ("NA", "Alpha"),
+            ("NA", "Beta"),
+            ("10.1000/b", "Delta"),
+        ]);
+    }
+
+    #[test]
+    fn multibyte_text_survives_every_format() {
+        let original = PaperResult {
+            doi: "10.1000/多言語".to_string(),
+            title: "蛋白质折叠の研究 🧬 — Faltung".to_string(),
+            authors: vec!["王小明".to_string(), "Zoë Ångström".to_string()],
+            url: "https://example.org/研究".to_string(),
+            score: 0.75,
+            reason: "Über Proteinfaltung 🔬".to_string(),
+            subject: "タンパク質".to_string(),
+            abstract_text: "本研究では🧪タンパク質の折り畳みを調べた。Résumé: ça marche.".to_string(),
+            timestamp: "2025-01-01 12:00:00".to_string(),
+            ..Default::default()
+        };
+
+        for format in [OutputFormat::Text, OutputFormat::Ris, OutputFormat::Jsonl, OutputFormat::Csv, OutputFormat::Markdown] {
+            let written = file_header(format, &original.subject) + &serialize_paper(&original, format);
+            // The BOM --output-bom puts in front is skipped on reading
+            let with_bom = format!("{}{}", BOM, written);
+            for contents in [&written, &with_bom] {
+                let read = parse_results(contents);
+                assert_eq!(read.len(), 1, "{:?}", format);
+                assert_eq!(read[0].title, original.title, "{:?}", format);
+                assert_eq!(read[0].abstract_text, original.abstract_text, "{:?}", format);
+                // CSV has no reason column
+                if format != OutputFormat::Csv {
+                    assert_eq!(read[0].reason, original.reason, "{:?}", format);
+                }
+                assert_eq!(read[0].authors, original.authors, "{:?}", format);
+                let rewritten = file_header(format, &original.subject) + &serialize_paper(&read[0], format);
+                assert_eq!(rewritten, written, "{:?}", format);
+            }
+        }
+    }
+
+    #[test]
+    fn newest_first_matches_the_whole_file_reversed() {
+        // Long abstracts so the papers span several of read_blocks_backwards' chunks
+        let papers: Vec<PaperResult> = (0..300)
+            .map(|i| PaperResult {
+                abstract_text: format!("{} ✓", "folding ".repeat(60 + i % 7)),
+                ..paper(&format!("10.1000/{}", i), &format!("Paper {}", i), 0.5, None, None)
+            })
+            .collect();
+        let path = std::env::temp_dir().join(format!("researcher-newest-{}.txt", std::process::id()));
+        let written: String = papers.iter().map(format_block).collect();
+        fs::write(&path, format!("{}{}", BOM, written)).unwrap();
+        let path = path.to_string_lossy().into_owned();
+
+        let mut expected = read_results(&path);
+        expected.reverse();
+        let dois = |papers: &[PaperResult]| papers.iter().map(|p| p.doi.clone()).collect::<Vec<_>>();
+
+        let (all, more) = read_newest_until(&path, |_| true, usize::MAX);
+        assert!(!more);
+        assert_eq!(dois(&all), dois(&expected));
+
+        let (page, more) = read_newest_until(&path, |_| true, 20);
+        assert!(more);
+        assert_eq!(dois(&page), dois(&expected[..20]));
+
+        let odd = |p: &PaperResult| p.doi.ends_with(['1', '3']);
+        let (filtered, more) = read_newest_until(&path, odd, 5);
+        assert!(more);
+        assert_eq!(dois(&filtered), ["10.1000/293", "10.1000/291", "10.1000/283", "10.1000/281", "10.1000/273"]);
+        assert_eq!(filtered[0].abstract_text, expected[6].abstract_text);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn merging_keeps_the_first_files_format() {
+        let dir = std::env::temp_dir().join(format!("researcher-merge-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = |name: &str| dir.join(name).to_string_lossy().into_owned();
+        let papers = corpus();
+        let ris: String = papers[..3].iter().map(|p| serialize_paper(p, OutputFormat::Ris)).collect();
+        let blocks: String = papers[2..].iter().map(format_block).collect();
+        fs::write(file("a.ris"), file_header(OutputFormat::Ris, "") + &ris).unwrap();
+        fs::write(file("b.txt"), blocks).unwrap();
+
+        let (read, written) = merge_files(&[file("a.ris"), file("b.txt")], &file("merged"), MergeStrategy::Score).unwrap();
+        assert_eq!((read, written), (8, 7));
+        let merged = fs::read_to_string(file("merged")).unwrap();
+        assert_eq!(detect_format(&merged), OutputFormat::Ris);
+        assert_eq!(parse_results(&merged).len(), 7);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,61 @@
+// Retries for requests to the metadata APIs and SearXNG, which rate-limit aggressively on
+// their public instances. A blip shouldn't cost a paper, so connection errors, timeouts,
+// 429s and 5xx responses are tried again with exponential backoff.
+
+use crate::DOIScraper;
+use crate::logs::Logger;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use tokio::time::{sleep, Duration};
+
+const FIRST_BACKOFF: Duration = Duration::from_millis(500);
+// A Retry-After longer than this is an instance that doesn't want us today
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
+// Sends `request` up to `max_retries` extra times. Whatever the last attempt got is returned,
+// so a response that's still a 429 or 5xx goes to the caller's usual status check
+pub async fn retry_request(request: RequestBuilder, max_retries: u32, logger: &Logger) -> reqwest::Result<Response> {
+    let mut attempt = 0;
+    loop {
+        // Only requests with a streamed body can't be cloned, none of ours have one
+        let Some(this_try) = request.try_clone() else {
+            return request.send().await;
+        };
+        let result = this_try.send().await;
+
+        let transient = match &result {
+            Ok(response) => response.status() == StatusCode::TOO_MANY_REQUESTS || response.status().is_server_error(),
+            Err(e) => e.is_connect() || e.is_timeout(),
+        };
+        if !transient || attempt >= max_retries {
+            return result;
+        }
+
+        // Capped at about 8.5 minutes so a large --max-retries can't overflow
+        let backoff = FIRST_BACKOFF * 2u32.pow(attempt.min(10));
+        let wait = match &result {
+            Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => retry_after(response).unwrap_or(backoff),
+            _ => backoff,
+        };
+        attempt += 1;
+        let (url, cause) = match &result {
+            Ok(response) => (Some(response.url()), response.status().to_string()),
+            Err(e) => (e.url(), e.to_string()),
+        };
+        let host = url.and_then(|u| u.host_str()).unwrap_or("request");
+        DOIScraper::warn(logger, &format!("      [HTTP] {} failed ({}), retrying in {:.1}s ({}/{})", host, cause, wait.as_secs_f32(), attempt, max_retries));
+        sleep(wait).await;
+    }
+}
+
+// Retry-After is either a number of seconds or an HTTP date
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+    let wait = match value.parse::<u64>() {
+        Ok(secs) => Duration::from_secs(secs),
+        Err(_) => {
+            let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+            (at.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().unwrap_or(Duration::ZERO)
+        }
+    };
+    Some(wait.min(MAX_RETRY_AFTER))
+}
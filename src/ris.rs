@@ -0,0 +1,170 @@
+// RIS records for --format ris, the import format EndNote, Zotero and Mendeley all read.
+// Relevance score, reason and subject have no RIS tag, they go in N1 notes so a RIS results file
+// can still be read back for dedup, sorting and export.
+
+use crate::results::PaperResult;
+use chrono::{Datelike, NaiveDate};
+
+pub fn format_record(paper: &PaperResult) -> String {
+    let mut record = String::new();
+    let mut line = |tag: &str, value: &str| {
+        let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !value.is_empty() {
+            record.push_str(&format!("{}  - {}\n", tag, value));
+        }
+    };
+
+    line("TY", if paper.resource_type.eq_ignore_ascii_case("dataset") { "DATA" } else { "JOUR" });
+    line("TI", &paper.title);
+    for author in &paper.authors {
+        line("AU", author);
+    }
+    // An arXiv ID isn't a DOI, reference managers get the PDF link instead
+    match paper.doi.strip_prefix("arXiv:") {
+        Some(arxiv) => line("L1", &format!("https://arxiv.org/pdf/{}", arxiv)),
+        None if paper.doi != "NA" => line("DO", &paper.doi),
+        None => {}
+    }
+    // L1 is the PDF link, skipped when it's the arXiv one written above
+    if let Some(url) = &paper.oa_url
+        && !paper.doi.strip_prefix("arXiv:").is_some_and(|arxiv| url.ends_with(arxiv))
+    {
+        line("L1", url);
+    }
+    line("UR", &paper.url);
+    line("AB", &paper.abstract_text);
+    for keyword in &paper.keywords {
+        line("KW", keyword);
+    }
+    if let Some(date) = paper.published {
+        line("PY", &date.year().to_string());
+        line("DA", &date.format("%Y/%m/%d/").to_string());
+    }
+    line("N1", &format!("Score: {:.2}", paper.score));
+    if !paper.reason.trim().is_empty() {
+        line("N1", &format!("Reason: {}", paper.reason));
+    }
+    if !paper.subject.is_empty() {
+        line("N1", &format!("Subject: {}", paper.subject));
+    }
+    line("Y2", &paper.timestamp);
+    record.push_str("ER  - \n\n");
+    record
+}
+
+// The first tag of every RIS record
+pub fn looks_like_ris(contents: &str) -> bool {
+    contents.lines()
+        .find(|l| !l.trim().is_empty())
+        .is_some_and(|l| l.starts_with("TY  -"))
+}
+
+pub fn parse(contents: &str) -> Vec<PaperResult> {
+    let mut results = Vec::new();
+    let mut current: Option<PaperResult> = None;
+
+    for raw in contents.lines() {
+        let Some((tag, value)) = raw.split_once("  -") else {
+            continue;
+        };
+        let value = value.trim();
+        match tag.trim() {
+            "TY" => {
+                current = Some(PaperResult {
+                    doi: "NA".to_string(),
+                    resource_type: if value == "DATA" { "dataset".to_string() } else { String::new() },
+                    ..Default::default()
+                });
+            }
+            "ER" => {
+                if let Some(paper) = current.take()
+                    && paper.score > 0.0
+                {
+                    results.push(paper);
+                }
+            }
+            tag => {
+                let Some(paper) = current.as_mut() else {
+                    continue;
+                };
+                match tag {
+                    "TI" | "T1" => paper.title = value.to_string(),
+                    "AU" | "A1" => paper.authors.push(value.to_string()),
+                    "DO" => paper.doi = value.to_string(),
+                    "L1" => {
+                        match value.strip_prefix("https://arxiv.org/pdf/") {
+                            Some(arxiv) if paper.doi == "NA" => paper.doi = format!("arXiv:{}", arxiv),
+                            _ => paper.oa_url = Some(value.to_string()),
+                        }
+                    }
+                    "UR" => paper.url = value.to_string(),
+                    "AB" => paper.abstract_text = value.to_string(),
+                    "KW" => paper.keywords.push(value.to_string()),
+                    "PY" if paper.published.is_none() => {
+                        paper.published = value.parse().ok().and_then(|year| NaiveDate::from_ymd_opt(year, 1, 1));
+                    }
+                    "DA" => {
+                        if let Ok(date) = NaiveDate::parse_from_str(value.trim_end_matches('/'), "%Y/%m/%d") {
+                            paper.published = Some(date);
+                        }
+                    }
+                    "N1" => {
+                        if let Some(score) = value.strip_prefix("Score: ") {
+                            paper.score = score.parse().unwrap_or(0.0);
+                        } else if let Some(reason) = value.strip_prefix("Reason: ") {
+                            paper.reason = reason.to_string();
+                        } else if let Some(subject) = value.strip_prefix("Subject: ") {
+                            paper.subject = subject.to_string();
+                        }
+                    }
+                    "Y2" => paper.timestamp = value.to_string(),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_title_doi_score_and_reason() {
+        let paper = PaperResult {
+            doi: "10.1016/S0140-6736(20)30183-5".to_string(),
+            title: "Clinical features of patients infected with 2019 novel coronavirus".to_string(),
+            authors: vec!["Chaolin Huang".to_string(), "Yeming Wang".to_string()],
+            url: "https://www.thelancet.com/article/S0140-6736(20)30183-5".to_string(),
+            score: 0.85,
+            reason: "Describes the clinical course,\nexactly the subject.".to_string(),
+            subject: "covid symptoms".to_string(),
+            published: NaiveDate::from_ymd_opt(2020, 2, 15),
+            timestamp: "2025-01-01 12:00:00".to_string(),
+            ..Default::default()
+        };
+
+        let record = format_record(&paper);
+        assert!(looks_like_ris(&record));
+        let parsed = parse(&record);
+
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].title, paper.title);
+        assert_eq!(parsed[0].doi, paper.doi);
+        assert_eq!(parsed[0].score, 0.85);
+        // RIS values are one line, the newline comes back as a space
+        assert_eq!(parsed[0].reason, "Describes the clinical course, exactly the subject.");
+        assert_eq!(parsed[0].authors, paper.authors);
+        assert_eq!(parsed[0].published, paper.published);
+    }
+
+    #[test]
+    fn arxiv_id_comes_back_from_the_pdf_link() {
+        let paper = PaperResult { doi: "arXiv:2101.00001".to_string(), title: "A preprint".to_string(), score: 0.7, ..Default::default() };
+        let parsed = parse(&format_record(&paper));
+        assert_eq!(parsed[0].doi, "arXiv:2101.00001");
+        assert_eq!(parsed[0].oa_url, None);
+    }
+}
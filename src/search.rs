@@ -0,0 +1,344 @@
+// Querying SearXNG. Kept apart from DOIScraper so a search can run in its own task and
+// hand over results while earlier ones are already being processed (--stream-results).
+
+use crate::retry::retry_request;
+use crate::{Args, DOIScraper, SearchMode, SearchResult, dates, useragent};
+use crate::logs::Logger;
+use anyhow::{Result, anyhow};
+use chrono::{Datelike, NaiveDate};
+use reqwest::Client;
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, Duration};
+
+#[derive(Clone)]
+pub struct Searcher {
+    client: Client,
+    args: Args,
+    logger: Logger,
+}
+
+impl Searcher {
+    pub fn new(client: Client, args: Args, logger: Logger) -> Self {
+        Self { client, args, logger }
+    }
+
+    // A searcher for one entry of a comma-separated --subject, the rest of the settings shared
+    pub fn for_subject(&self, subject: &str) -> Self {
+        let mut searcher = self.clone();
+        searcher.args.subject = subject.to_string();
+        searcher
+    }
+
+    // Every batch collected and pooled, for the usual search-then-process flow
+    pub async fn search(&self) -> Result<Vec<SearchResult>> {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        self.search_batches(tx).await?;
+
+        // Repeats of the same URL (another instance, another year) are folded together so each
+        // result knows every engine and instance that returned it
+        let mut merged: Vec<SearchResult> = Vec::new();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        let mut batches = 0;
+        while let Some(batch) = rx.recv().await {
+            batches += 1;
+            for result in batch {
+                match seen.get(&result.url_key()) {
+                    Some(&i) => merged[i].absorb(&result),
+                    None => {
+                        seen.insert(result.url_key(), merged.len());
+                        merged.push(result);
+                    }
+                }
+            }
+        }
+
+        if batches > 1 {
+            DOIScraper::log(&self.logger, &format!("Merged into {} unique results\n", merged.len()));
+        }
+        Ok(merged)
+    }
+
+    // Sends each search pass's results the moment it finishes. Only fails when every pass did,
+    // a receiver that hung up (enough results already) just means nobody needs the rest
+    pub async fn search_batches(&self, tx: mpsc::UnboundedSender<Vec<SearchResult>>) -> Result<()> {
+        let (first_year, last_year) = match self.bounds() {
+            Some((start, end)) if self.args.split_by_year && start.year() < end.year() => (start.year(), end.year()),
+            _ => {
+                let _ = tx.send(self.search_instances(None).await?);
+                return Ok(());
+            }
+        };
+
+        // SearXNG can only filter to the last 12 months, so each year is searched without a
+        // filter and with the year in the query instead
+        let years = (last_year - first_year + 1) as usize;
+        DOIScraper::log(&self.logger, &format!("Splitting '{}' into {} per-year searches", self.args.time_range, years));
+
+        let mut failures = 0;
+        for year in (first_year..=last_year).rev() {
+            match self.search_instances(Some(year)).await {
+                Ok(results) => {
+                    DOIScraper::log(&self.logger, &format!("{}: {} results", year, results.len()));
+                    if tx.send(results).is_err() {
+                        return Ok(());
+                    }
+                }
+                Err(e) => {
+                    DOIScraper::warn(&self.logger, &format!("Search for {} failed: {}", year, e));
+                    failures += 1;
+                }
+            }
+        }
+
+        if failures == years {
+            return Err(anyhow!("All {} per-year searches failed", years));
+        }
+        Ok(())
+    }
+
+    // --balance-engines picks from a wider pool than it keeps
+    fn wanted_results(&self) -> usize {
+        if self.args.balance_engines && !self.args.stream_results {
+            self.args.max_results.saturating_mul(2)
+        } else {
+            self.args.max_results
+        }
+    }
+
+    // The exact dates asked for, checked in DOIScraper::new so a bad range never gets here
+    fn bounds(&self) -> Option<(NaiveDate, NaiveDate)> {
+        dates::time_range_bounds(&self.args.time_range, chrono::Local::now().date_naive()).ok().flatten()
+    }
+
+    // SearXNG only filters to "newer than a day/week/month/year ago". Date bounds get the
+    // narrowest of those that still reaches back to their start, or no filter when none does,
+    // the exact bounds are applied to publication dates after enrichment
+    fn searxng_time_range(&self) -> Option<&'static str> {
+        let time_range = self.args.time_range.trim();
+        if let Some(standard) = ["day", "week", "month", "year"].into_iter().find(|r| *r == time_range) {
+            DOIScraper::log(&self.logger, &format!("Applying time filter: {}\n", standard));
+            return Some(standard);
+        }
+
+        let Some((start, _)) = self.bounds() else {
+            if !time_range.is_empty() {
+                DOIScraper::warn(&self.logger, &format!("Warning: Invalid time range '{}'. Valid options: day, week, month, year, Nyear or YYYY-MM-DD:YYYY-MM-DD", time_range));
+                DOIScraper::log(&self.logger, "   Continuing without time filter\n");
+            }
+            return None;
+        };
+
+        let age = (chrono::Local::now().date_naive() - start).num_days();
+        let closest = [(1, "day"), (7, "week"), (31, "month"), (366, "year")]
+            .into_iter()
+            .find(|(days, _)| age <= *days)
+            .map(|(_, range)| range);
+        match closest {
+            Some(range) => DOIScraper::log(&self.logger, &format!("Applying time filter: {} (exact range '{}' checked after enrichment)\n", range, time_range)),
+            None => DOIScraper::log(&self.logger, &format!("No SearXNG time filter reaches back to {}, searching all time and checking '{}' after enrichment\n", start, time_range)),
+        }
+        closest
+    }
+
+    async fn search_instances(&self, year: Option<i32>) -> Result<Vec<SearchResult>> {
+        if !self.args.merge_instances {
+            return self.search_instance(&self.args.instance, year).await;
+        }
+
+        let instances: Vec<&str> = self.args.instance.split(',')
+            .map(|i| i.trim())
+            .filter(|i| !i.is_empty())
+            .collect();
+        DOIScraper::log(&self.logger, &format!("Querying {} instances in parallel", instances.len()));
+
+        let responses = futures::future::join_all(instances.iter().map(|i| self.search_instance(i, year))).await;
+
+        let mut merged: Vec<SearchResult> = Vec::new();
+        let mut failures = 0;
+        for (instance, response) in instances.iter().zip(responses) {
+            let results = match response {
+                Ok(results) => results,
+                Err(e) => {
+                    DOIScraper::warn(&self.logger, &format!("Instance {} failed: {}", instance, e));
+                    failures += 1;
+                    continue;
+                }
+            };
+
+            for mut result in results {
+                let key = result.url_key();
+                result.instances = vec![instance.to_string()];
+                match merged.iter_mut().find(|m| m.url_key() == key) {
+                    Some(existing) => existing.absorb(&result),
+                    None => merged.push(result),
+                }
+            }
+        }
+
+        if failures == instances.len() {
+            return Err(anyhow!("All {} SearXNG instances failed", instances.len()));
+        }
+
+        DOIScraper::log(&self.logger, &format!("Merged into {} unique results\n", merged.len()));
+        Ok(merged)
+    }
+
+    // `year` is set for one pass of --split-by-year, which replaces the time filter
+    async fn search_instance(&self, instance: &str, year: Option<i32>) -> Result<Vec<SearchResult>> {
+        DOIScraper::log(&self.logger, &format!("Searching SearXNG instance {}\n", instance));
+
+        let mut query = match self.args.search_mode {
+            SearchMode::Topic => self.args.subject.clone(),
+            // Quoted phrase, engines that support operators match it exactly
+            SearchMode::Title => format!("\"{}\"", self.args.subject.trim_matches('"')),
+            // Google Scholar's operator, the other engines just treat it as text containing the name
+            SearchMode::Author => format!("author:\"{}\"", self.args.subject.trim_matches('"')),
+        };
+        if let Some(year) = year {
+            query = format!("{} {}", query, year);
+        }
+        
+        let mut params = vec![
+            ("q", query.as_str()),
+            ("format", "json"),
+            ("categories", self.args.category.as_str()),
+            ("engines", self.args.engines.as_str()),
+        ];
+
+        if year.is_none()
+            && let Some(time_range) = self.searxng_time_range()
+        {
+            params.push(("time_range", time_range));
+        }
+
+        let url = format!("{}/search", instance.trim_end_matches('/'));
+        
+        DOIScraper::debug(&self.logger, &format!("[DEBUG] URL: {}", url));
+        DOIScraper::debug(&self.logger, &format!("[DEBUG] Params: {:?}\n", params));
+
+        // Most instances return about 20 results a page, so larger --max-results need more pages.
+        // Stops early at an empty page, which is where the engines ran out
+        let mut results: Vec<SearchResult> = Vec::new();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for pageno in 1..=self.args.max_pages.max(1) {
+            if pageno > 1 {
+                // A polite gap, public instances throttle rapid paging first
+                sleep(Duration::from_secs(1)).await;
+            }
+            let page = pageno.to_string();
+            let mut page_params = params.clone();
+            page_params.push(("pageno", page.as_str()));
+
+            let page_results = match self.search_page(instance, &url, &page_params).await {
+                Ok(page_results) => page_results,
+                // What the earlier pages found is still worth processing
+                Err(e) if pageno > 1 => {
+                    DOIScraper::warn(&self.logger, &format!("Page {} failed, keeping the first {}: {}", pageno, pageno - 1, e));
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+            if page_results.is_empty() {
+                break;
+            }
+
+            let before = results.len();
+            // SearXNG sometimes repeats a result on the next page
+            for result in page_results {
+                match seen.get(&result.url_key()) {
+                    Some(&i) => results[i].absorb(&result),
+                    None => {
+                        seen.insert(result.url_key(), results.len());
+                        results.push(result);
+                    }
+                }
+            }
+            DOIScraper::debug(&self.logger, &format!("[DEBUG] Page {}: {} new results", pageno, results.len() - before));
+            // Instances that ignore pageno hand back page 1 again
+            if results.len() == before || results.len() >= self.wanted_results() {
+                break;
+            }
+        }
+        DOIScraper::log(&self.logger, &format!("Found {} results from SearXNG\n", results.len()));
+        
+        if !results.is_empty() {
+            DOIScraper::debug(&self.logger, &format!("[DEBUG] First result engine: {}", results[0].engine));
+        }
+        
+        Ok(results)
+    }
+
+    // One page of results, retried while the instance answers with HTML instead of JSON
+    async fn search_page(&self, instance: &str, url: &str, params: &[(&str, &str)]) -> Result<Vec<SearchResult>> {
+        let mut attempt = 0;
+        let data: serde_json::Value = loop {
+            let request = self.client
+                .get(url)
+                .query(params)
+                .header("Accept", "application/json")
+                .header("User-Agent", useragent::pick(&self.args.user_agent))
+                .timeout(Duration::from_secs(self.args.search_timeout));
+            let response = retry_request(request, self.args.max_retries, &self.logger).await?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_body = response.text().await.unwrap_or_else(|_| "Unable to read error body".to_string());
+                let error_msg = format!("\nSearXNG Request Failed:\n   Status: {}\n   URL: {}\n   Params: {:?}\n   Error body: {}\n", status, url, params, error_body);
+                DOIScraper::warn(&self.logger, &error_msg);
+                return Err(anyhow!("SearXNG error: {} - {}", status, error_body));
+            }
+
+            // Overloaded public instances answer 200 with an anti-bot HTML page. A missing
+            // content type is tolerated, some proxies strip it
+            let wrong_type = response.headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| !v.contains("json"));
+            let body = response.text().await?;
+            let looks_like_json = body.trim_start().starts_with(['{', '[']);
+
+            if !wrong_type && looks_like_json {
+                break serde_json::from_str(&body)?;
+            }
+
+            DOIScraper::log(&self.logger, &format!("Instance {} returned non-JSON (possible rate-limit/captcha)", instance));
+            DOIScraper::debug(&self.logger, &format!("[DEBUG] Body starts with: {}", DOIScraper::safe_truncate(body.trim(), 200)));
+            if attempt >= self.args.searxng_retries {
+                return Err(anyhow!("{} kept returning non-JSON after {} attempts", instance, attempt + 1));
+            }
+            attempt += 1;
+            let backoff = Duration::from_secs(2u64.pow(attempt));
+            DOIScraper::log(&self.logger, &format!("   Retrying in {}s ({}/{})", backoff.as_secs(), attempt, self.args.searxng_retries));
+            sleep(backoff).await;
+        };
+        self.extract_results(&data)
+    }
+
+    // Some forks/proxies nest the array (e.g. `data.results`), so probe the known spots
+    fn extract_results(&self, data: &serde_json::Value) -> Result<Vec<SearchResult>> {
+        let paths = if !self.args.results_json_path.is_empty() {
+            vec![self.args.results_json_path.as_str()]
+        } else {
+            vec!["results", "data.results"]
+        };
+
+        for path in &paths {
+            let mut node = Some(data);
+            for key in path.split('.').filter(|k| !k.is_empty()) {
+                node = node.and_then(|n| n.get(key));
+            }
+
+            if let Some(array) = node.filter(|n| n.is_array()) {
+                DOIScraper::debug(&self.logger, &format!("[DEBUG] Results found at: {}", path));
+                let mut results: Vec<SearchResult> = serde_json::from_value(array.clone())?;
+                for result in &mut results {
+                    result.subject = self.args.subject.clone();
+                }
+                return Ok(results);
+            }
+        }
+
+        Err(anyhow!("No results array found in SearXNG response (tried: {})", paths.join(", ")))
+    }
+}
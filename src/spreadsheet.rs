@@ -0,0 +1,76 @@
+// CSV rows for --format csv, for spreadsheets. The columns are fixed so a sheet built on one
+// run's file still lines up with the next, the header is written once when the file is new.
+// The abstract is the last column, it's the one nobody wants to scroll past.
+
+use crate::results::PaperResult;
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+pub const HEADER: &str = "doi,title,authors,year,url,score,saved,abstract\n";
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Row {
+    doi: String,
+    title: String,
+    // "; " between names, the same as the text format's Authors line
+    authors: String,
+    year: Option<i32>,
+    url: String,
+    score: f32,
+    saved: String,
+    #[serde(rename = "abstract")]
+    abstract_text: String,
+}
+
+pub fn format_record(paper: &PaperResult) -> String {
+    let row = Row {
+        doi: paper.doi.clone(),
+        title: paper.title.clone(),
+        authors: paper.authors.join("; "),
+        year: paper.published.map(|date| date.year()),
+        url: paper.url.clone(),
+        score: (paper.score * 100.0).round() / 100.0,
+        saved: paper.timestamp.clone(),
+        abstract_text: paper.abstract_text.clone(),
+    };
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    // Writing into memory can't fail
+    let _ = writer.serialize(&row);
+    writer.into_inner()
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .unwrap_or_default()
+}
+
+pub fn looks_like_csv(contents: &str) -> bool {
+    contents.lines()
+        .find(|l| !l.trim().is_empty())
+        .is_some_and(|l| l.trim_end() == HEADER.trim_end())
+}
+
+// Only the year survives the trip, it comes back as January 1st
+pub fn parse(contents: &str) -> Vec<PaperResult> {
+    csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(contents.as_bytes())
+        .deserialize::<Row>()
+        .filter_map(|row| row.ok())
+        .map(|r| PaperResult {
+            doi: if r.doi.trim().is_empty() { "NA".to_string() } else { r.doi },
+            title: r.title,
+            authors: r.authors.split("; ")
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty())
+                .collect(),
+            url: r.url,
+            score: r.score,
+            abstract_text: r.abstract_text,
+            published: r.year.and_then(|year| NaiveDate::from_ymd_opt(year, 1, 1)),
+            timestamp: r.saved,
+            ..Default::default()
+        })
+        .filter(|paper| paper.score > 0.0)
+        .collect()
+}
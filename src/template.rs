@@ -0,0 +1,45 @@
+// --template-file: renders each saved paper through a user's Handlebars template and appends
+// the result, for layouts none of the built-in formats cover (wiki markup, custom reports).
+// Every PaperResult field is available by name: {{title}}, {{doi}}, {{score}}, {{keywords}}, ...
+
+use crate::results::PaperResult;
+use anyhow::{Result, anyhow};
+use handlebars::Handlebars;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+pub struct TemplateWriter {
+    registry: Handlebars<'static>,
+    output: String,
+}
+
+impl TemplateWriter {
+    // Fails on syntax errors, unknown helpers and misspelled fields up front instead of on the first save
+    pub fn load(template_path: &str, output: &str) -> Result<Self> {
+        let mut registry = Handlebars::new();
+        registry.set_strict_mode(true);
+        // The output is plain text in whatever syntax the user wants, not HTML
+        registry.register_escape_fn(handlebars::no_escape);
+        registry.register_template_file("paper", template_path)
+            .map_err(|e| anyhow!("could not load template {}: {}", template_path, e))?;
+
+        registry.render("paper", &PaperResult::default())
+            .map_err(|e| anyhow!("template {} does not render: {}", template_path, e))?;
+
+        Ok(Self { registry, output: output.to_string() })
+    }
+
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    pub fn append(&self, paper: &PaperResult) -> Result<()> {
+        let rendered = self.registry.render("paper", paper)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.output)?;
+        write!(file, "{}", rendered)?;
+        Ok(())
+    }
+}
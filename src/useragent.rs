@@ -0,0 +1,34 @@
+// Browser user agents for the requests that look like browsing: SearXNG searches and fetching
+// result pages. A fresh one is picked per request, a whole run on one UA is easy for a WAF to
+// fingerprint. --user-agent pins a single one instead.
+
+const POOL: &[&str] = &[
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36",
+    "Mozilla/5.0 (X11; Ubuntu; Linux x86_64) AppleWebKit/537.36",
+    "Mozilla/5.0 (Linux; Android 14; Pixel 7) AppleWebKit/537.36",
+    "Mozilla/5.0 (iPhone; CPU iPhone OS 17_2 like Mac OS X) AppleWebKit/537.36",
+    "Mozilla/5.0 (Windows NT 6.1; Win64; x64) AppleWebKit/537.36",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 11_6) AppleWebKit/537.36",
+    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36",
+    "Mozilla/5.0 (Linux; Android 13; SM-G991B) AppleWebKit/537.36",
+    "Mozilla/5.0 (iPad; CPU OS 16_6 like Mac OS X) AppleWebKit/537.36",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 12_5_1) AppleWebKit/537.36",
+    "Mozilla/5.0 (X11; Fedora; Linux x86_64) AppleWebKit/537.36",
+    "Mozilla/5.0 (Linux; Android 12; OnePlus 9) AppleWebKit/537.36",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_14_6) AppleWebKit/537.36",
+    "Mozilla/5.0 (Linux; Android 11; Nokia X20) AppleWebKit/537.36",
+    "Mozilla/5.0 (Windows NT 6.3; Win64; x64) AppleWebKit/537.36",
+    "Mozilla/5.0 (X11; CrOS x86_64 15604.45.0) AppleWebKit/537.36",
+    "Mozilla/5.0 (Windows NT 10.0) AppleWebKit/537.36",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_13_6) AppleWebKit/537.36",
+];
+
+// --user-agent when it's set, otherwise a random one from the pool
+pub fn pick(fixed: &str) -> &str {
+    if !fixed.trim().is_empty() {
+        return fixed.trim();
+    }
+    POOL[fastrand::usize(..POOL.len())]
+}
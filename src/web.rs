@@ -1,22 +1,21 @@
 // A lot of the code here is taken from an older project: https://github.com/Servus-Altissimi/marktplaats-monitor
 
-use crate::{DOIScraper, Args};
+use crate::{DOIScraper, Args, SearchProgress};
+use crate::logs::{self, LogBuffer, LogEntry, LogLevel};
+use crate::ratelimit::RateLimiter;
+use crate::db::ResultsDb;
+use crate::results::{self, PaperResult, read_results};
+use std::convert::Infallible;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use std::fs;
-use std::io::{BufRead, BufReader};
+use warp::http::StatusCode;
 use warp::{Filter, Reply};
 use serde::{Deserialize, Serialize};
 use chrono::Local;
-
-#[derive(Debug, Serialize)]
-struct PaperResult {
-    doi: String,
-    title: String,
-    url: String,
-    score: f32,
-    abstract_text: String,
-    timestamp: String,
-}
+use tokio::sync::{broadcast, Semaphore};
+use futures::{SinkExt, StreamExt};
+use warp::ws::{Message, WebSocket};
 
 #[derive(Debug, Serialize)]
 struct StatusMessage {
@@ -24,9 +23,33 @@ struct StatusMessage {
     message: String,
 }
 
+// GET /health, for reverse proxies and container healthchecks
+#[derive(Serialize)]
+struct Health {
+    status: &'static str,
+    version: &'static str,
+    uptime_secs: u64,
+}
+
 #[derive(Debug, Deserialize)]
 struct SearchQuery {
     q: Option<String>,
+    // "score", "citations", "date" (published) or "title", saving order otherwise
+    sort: Option<String>,
+    // "asc" or "desc". Without it titles go A to Z and everything else highest/newest first
+    order: Option<String>,
+    limit: Option<usize>,
+    #[serde(default)]
+    offset: usize,
+}
+
+// One page of /results. `total` counts every match so the UI can say how many are left, it's
+// null when the file was only read as far as this page
+#[derive(Serialize)]
+struct ResultsPage {
+    total: Option<usize>,
+    more: bool,
+    results: Vec<PaperResult>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -43,16 +66,40 @@ struct SearchRequest {
     ollama_url: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct DeleteRequest {
+    doi: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct ValidateRequest {
     url: String,
     service_type: String,
 }
 
-pub async fn start_web_server(port: u16) {
-    let logs: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+pub async fn start_web_server(defaults: Args, limiter: Arc<RateLimiter>) {
+    let port = defaults.web_poort;
+    let started = Instant::now();
+    let logs = Arc::new(LogBuffer::new());
+    // Every search appends to the same results file, so only let a few run at once (one by default)
+    let searches = Arc::new(Semaphore::new(defaults.max_web_searches.max(1)));
+    // Saved papers as JSON, fanned out to every connected WebSocket
+    let (paper_events, _) = broadcast::channel::<String>(64);
+    // With --max-web-searches above 1 this follows whichever search started last
+    let progress = Arc::new(Mutex::new(SearchProgress::default()));
     
     let logs_filter = warp::any().map(move || logs.clone());
+    let limiter_filter = warp::any().map(move || limiter.clone());
+    let searches_filter = warp::any().map(move || searches.clone());
+    // --db the server was started with, read by the results tab instead of the output file
+    let db_path = defaults.db.clone();
+    let db_filter = warp::any().map(move || db_path.clone());
+    // --output, where web searches save, so the results tab reads, clears and deletes from there
+    let output = defaults.output.clone();
+    let output_filter = warp::any().map(move || output.clone());
+    let defaults_filter = warp::any().map(move || defaults.clone());
+    let events_filter = warp::any().map(move || paper_events.clone());
+    let progress_filter = warp::any().map(move || progress.clone());
 
     let index = warp::get()
         .and(warp::path::end())
@@ -64,194 +111,316 @@ pub async fn start_web_server(port: u16) {
     let results = warp::get()
         .and(warp::path("results"))
         .and(warp::query::<SearchQuery>())
+        .and(db_filter.clone())
+        .and(output_filter.clone())
         .and_then(get_results);
 
     let search = warp::post()
         .and(warp::path("search"))
         .and(warp::body::json())
         .and(logs_filter.clone())
-        .map(|request: SearchRequest, logs: Arc<Mutex<Vec<String>>>| {
+        .and(limiter_filter)
+        .and(searches_filter.clone())
+        .and(defaults_filter)
+        .and(events_filter.clone())
+        .and(progress_filter.clone())
+        .map(|request: SearchRequest, logs: Arc<LogBuffer>, limiter: Arc<RateLimiter>, searches: Arc<Semaphore>, defaults: Args, paper_events: broadcast::Sender<String>, progress: Arc<Mutex<SearchProgress>>| {
+            let permit = match searches.try_acquire_owned() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    return warp::reply::with_status(warp::reply::json(&StatusMessage {
+                        status: "busy".to_string(),
+                        message: "A search is already running, wait for it to finish".to_string(),
+                    }), StatusCode::CONFLICT);
+                }
+            };
+
             let args = Args {
                 subject: request.subject.clone(),
                 instance: request.instance,
                 max_results: request.max_results,
-                output: "results.txt".to_string(),
                 model: request.model,
                 no_ai: request.no_ai,
                 time_range: request.time_range,
                 category: request.category,
                 engines: request.engines,
                 min_score: request.min_score,
-                verbose: false,
-                web_poort: 6601,
                 ollama_url: request.ollama_url,
+                // The form's subject, not the server's --query-file
+                queries: Vec::new(),
+                // Everything the form doesn't expose keeps what the server was started with
+                ..defaults
             };
             
-            add_log(&logs, &format!("Starting search for: {}", request.subject));
+            add_log(&logs, LogLevel::Info, &format!("Starting search for: {}", request.subject));
+            *progress.lock().unwrap() = SearchProgress { running: true, ..Default::default() };
             
             tokio::spawn(async move {
-                add_log(&logs, "Initializing scraper...");
+                // Held until the search ends, however it ends
+                let _permit = permit;
+                add_log(&logs, LogLevel::Info, "Initializing scraper...");
                 
-                match DOIScraper::new_with_logger(args, Some(logs.clone())).await {
+                match DOIScraper::new_with_logger(args, Some(logs.clone()), Some(limiter)).await {
                     Ok(mut scraper) => {
-                        add_log(&logs, "Scraper initialized successfully");
-                        add_log(&logs, "Beginning search!");
+                        scraper.set_paper_events(paper_events);
+                        scraper.set_progress(progress.clone());
+                        add_log(&logs, LogLevel::Info, "Scraper initialized successfully");
+                        add_log(&logs, LogLevel::Info, "Beginning search!");
                         
                         match scraper.run().await {
-                            Ok(_) => add_log(&logs, "Search completed!"),
-                            Err(e) => add_log(&logs, &format!("Search error: {}", e)),
+                            Ok(_) => add_log(&logs, LogLevel::Info, "Search completed!"),
+                            Err(e) => add_log(&logs, LogLevel::Error, &format!("Search error: {}", e)),
                         }
                     }
-                    Err(e) => add_log(&logs, &format!("Failed to init scraper: {}", e)),
+                    Err(e) => add_log(&logs, LogLevel::Error, &format!("Failed to init scraper: {}", e)),
                 }
+                // Whichever way it ended, including a scraper that never started
+                progress.lock().unwrap().running = false;
             });
             
-            warp::reply::json(&StatusMessage {
+            warp::reply::with_status(warp::reply::json(&StatusMessage {
                 status: "ok".to_string(),
                 message: "Search started in background".to_string(),
-            })
+            }), StatusCode::OK)
         });
 
     let clear = warp::post()
         .and(warp::path("clear_results"))
+        .and(db_filter.clone())
+        .and(output_filter.clone())
         .and_then(clear_all_results);
 
+    let delete = warp::post()
+        .and(warp::path("delete"))
+        .and(warp::body::json())
+        .and(db_filter)
+        .and(output_filter)
+        .and_then(delete_result);
+
     let validate = warp::post()
         .and(warp::path("validate"))
         .and(warp::body::json())
         .and_then(validate_service);
 
+    let busy = warp::get()
+        .and(warp::path("busy"))
+        .and(searches_filter)
+        .map(|searches: Arc<Semaphore>| {
+            warp::reply::json(&serde_json::json!({ "busy": searches.available_permits() == 0 }))
+        });
+
+    let status = warp::get()
+        .and(warp::path("status"))
+        .and(progress_filter)
+        .map(|progress: Arc<Mutex<SearchProgress>>| {
+            warp::reply::json(&progress.lock().unwrap().clone())
+        });
+
+    let ws = warp::path("ws")
+        .and(warp::ws())
+        .and(events_filter)
+        .map(|ws: warp::ws::Ws, paper_events: broadcast::Sender<String>| {
+            // Subscribing here means a (re)connecting client only sees papers saved from now on
+            let events = paper_events.subscribe();
+            ws.on_upgrade(move |socket| push_papers(socket, events))
+        });
+
     let get_logs = warp::get()
         .and(warp::path("logs"))
+        .and(warp::path::end())
+        .and(logs_filter.clone())
+        .map(|logs: Arc<LogBuffer>| warp::reply::json(&logs.lines()));
+
+    let stream_logs = warp::get()
+        .and(warp::path!("logs" / "stream"))
         .and(logs_filter.clone())
-        .map(|logs: Arc<Mutex<Vec<String>>>| {
-            let logs = logs.lock().unwrap();
-            warp::reply::json(&*logs)
+        .map(|logs: Arc<LogBuffer>| {
+            warp::sse::reply(warp::sse::keep_alive().stream(log_events(logs.subscribe())))
+        });
+
+    // The whole buffer as a text file, for attaching to bug reports
+    let download_logs = warp::get()
+        .and(warp::path!("logs" / "download"))
+        .and(logs_filter.clone())
+        .map(|logs: Arc<LogBuffer>| {
+            let filename = format!("researcher-logs-{}.txt", Local::now().format("%Y%m%d-%H%M%S"));
+            let reply = warp::reply::with_header(logs_as_text(&logs.lines()), "Content-Type", "text/plain; charset=utf-8");
+            warp::reply::with_header(reply, "Content-Disposition", format!("attachment; filename=\"{}\"", filename))
+        });
+
+    let health = warp::get()
+        .and(warp::path("health"))
+        .and(warp::path::end())
+        .map(move || {
+            warp::reply::json(&Health {
+                status: "ok",
+                version: env!("CARGO_PKG_VERSION"),
+                uptime_secs: started.elapsed().as_secs(),
+            })
         });
 
     let routes = index
         .or(results)
         .or(search)
         .or(clear)
+        .or(delete)
         .or(validate)
-        .or(get_logs);
+        .or(busy)
+        .or(status)
+        .or(ws)
+        .or(get_logs)
+        .or(stream_logs)
+        .or(download_logs)
+        .or(health);
 
     println!("Web interface running on http://localhost:{}", port);
     warp::serve(routes).run(([127, 0, 0, 1], port)).await;
 }
 
-async fn get_results(query: SearchQuery) -> Result<impl Reply, warp::Rejection> {
-    let filepath = "results.txt";
-    let mut results = Vec::new();
-    
-    if let Ok(file) = fs::File::open(filepath) {
-        let reader = BufReader::new(file);
-        let mut current_paper: Option<PaperResult> = None;
-        let mut abstract_lines = Vec::new();
-        let mut in_abstract = false;
-        
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                if line.starts_with("====") {
-                    if let Some(mut paper) = current_paper.take() {
-                        if !abstract_lines.is_empty() {
-                            paper.abstract_text = abstract_lines.join(" ").trim().to_string();
-                            abstract_lines.clear();
-                        }
-                        
-                        // Only add papers with score > 0.0, redundant safeguard,
-                        if paper.score > 0.0 {
-                            results.push(paper);
-                        }
-                    }
-                    in_abstract = false;
-                    current_paper = Some(PaperResult {
-                        doi: String::new(),
-                        title: String::new(),
-                        url: String::new(),
-                        score: 0.0,
-                        abstract_text: String::new(),
-                        timestamp: String::new(),
-                    });
-                } else if line.starts_with("DOI: ") {
-                    if let Some(ref mut paper) = current_paper {
-                        paper.doi = line.trim_start_matches("DOI: ").to_string();
-                    }
-                    in_abstract = false;
-                } else if line.starts_with("Title: ") {
-                    if let Some(ref mut paper) = current_paper {
-                        paper.title = line.trim_start_matches("Title: ").to_string();
-                    }
-                    in_abstract = false;
-                } else if line.starts_with("URL: ") {
-                    if let Some(ref mut paper) = current_paper {
-                        paper.url = line.trim_start_matches("URL: ").to_string();
-                    }
-                    in_abstract = false;
-                } else if line.starts_with("Score: ") {
-                    if let Some(ref mut paper) = current_paper {
-                        if let Ok(score) = line.trim_start_matches("Score: ").parse::<f32>() {
-                            paper.score = score;
-                        }
-                    }
-                    in_abstract = false;
-                } else if line.starts_with("Saved: ") {
-                    if let Some(ref mut paper) = current_paper {
-                        paper.timestamp = line.trim_start_matches("Saved: ").to_string();
+// Parsing a big results file is blocking work, it runs off the async workers
+async fn get_results(query: SearchQuery, db_path: String, output: String) -> Result<impl Reply, warp::Rejection> {
+    let page = tokio::task::spawn_blocking(move || results_page(&query, &db_path, &output))
+        .await
+        .map_err(|_| warp::reject())?;
+    Ok(warp::reply::json(&page))
+}
+
+fn results_page(query: &SearchQuery, db_path: &str, output: &str) -> ResultsPage {
+    let sort = query.sort.as_deref().unwrap_or("");
+    let descending = match query.order.as_deref() {
+        Some("asc") => false,
+        Some("desc") => true,
+        _ => sort != "title",
+    };
+    let search_lower = query.q.as_deref().map(str::to_lowercase);
+    let matches = |r: &PaperResult| search_lower.as_ref().is_none_or(|search_lower| {
+        r.title.to_lowercase().contains(search_lower) ||
+        r.abstract_text.to_lowercase().contains(search_lower) ||
+        r.doi.to_lowercase().contains(search_lower)
+    });
+
+    // Saving order, newest first by default, is the file's own order read from one end or the
+    // other, only this page needs reading
+    if db_path.is_empty() && sort.is_empty()
+        && let Some(limit) = query.limit
+    {
+        let wanted = query.offset.saturating_add(limit);
+        let (results, more) = if descending {
+            results::read_newest_until(output, matches, wanted)
+        } else {
+            results::read_results_until(output, matches, wanted)
+        };
+        let total = (!more).then_some(results.len());
+        let results = results.into_iter().skip(query.offset).collect();
+        return ResultsPage { total, more, results };
+    }
+
+    let mut results = if !db_path.is_empty() {
+        // LIKE does the filtering, no need to load every paper first
+        ResultsDb::open(db_path)
+            .and_then(|db| db.papers(query.q.as_deref()))
+            .unwrap_or_default()
+    } else {
+        let mut results = read_results(output);
+        results.retain(|r| matches(r));
+        results
+    };
+
+    // Sorted ascending here, then reversed for descending
+    match sort {
+        "score" => results.sort_by(|a, b| a.score.total_cmp(&b.score)),
+        // Relevance breaks ties, as with --sort-by citations
+        "citations" => results.sort_by(|a, b| a.cited_by.cmp(&b.cited_by).then(a.score.total_cmp(&b.score))),
+        "date" => results.sort_by_key(|p| p.published),
+        "title" => results.sort_by_cached_key(|p| p.title.to_lowercase()),
+        _ => {}
+    }
+    if descending {
+        results.reverse();
+    }
+    // Undated papers and ones without a count go last in either direction. The sort is stable,
+    // the rest keep their order
+    match sort {
+        "citations" => results.sort_by_key(|p| p.cited_by.is_none()),
+        "date" => results.sort_by_key(|p| p.published.is_none()),
+        _ => {}
+    }
+
+    let total = results.len();
+    let results: Vec<PaperResult> = results.into_iter()
+        .skip(query.offset)
+        .take(query.limit.unwrap_or(usize::MAX))
+        .collect();
+    let more = query.offset + results.len() < total;
+    ResultsPage { total: Some(total), more, results }
+}
+
+async fn push_papers(socket: WebSocket, mut events: broadcast::Receiver<String>) {
+    let (mut sender, mut receiver) = socket.split();
+
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(json) => {
+                    if sender.send(Message::text(json)).await.is_err() {
+                        break;
                     }
-                    in_abstract = false;
-                } else if line.starts_with("Abstract:") {
-                    in_abstract = true;
-                    abstract_lines.clear();
-                } else if in_abstract && !line.trim().is_empty() {
-                    abstract_lines.push(line.trim().to_string());
                 }
-            }
-        }
-        
-        if let Some(mut paper) = current_paper {
-            if !abstract_lines.is_empty() {
-                paper.abstract_text = abstract_lines.join(" ").trim().to_string();
-            }
-            // Only add papers with score > 0.0, redundant safeguard,
-            if paper.score > 0.0 {
-                results.push(paper);
-            }
+                // A slow client just misses some, the Results tab still has everything
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = receiver.next() => match incoming {
+                Some(Ok(message)) if !message.is_close() => continue,
+                _ => break,
+            },
         }
     }
-    
-    if let Some(search_term) = query.q {
-        let search_lower = search_term.to_lowercase();
-        results.retain(|r| {
-            r.title.to_lowercase().contains(&search_lower) ||
-            r.abstract_text.to_lowercase().contains(&search_lower) ||
-            r.doi.to_lowercase().contains(&search_lower)
-        });
-    }
-    
-    results.reverse();
-    
-    Ok(warp::reply::json(&results))
 }
 
-fn add_log(logs: &Arc<Mutex<Vec<String>>>, message: &str) {
-    let timestamp = Local::now().format("%H:%M:%S");
-    let log_entry = format!("[{}] {}", timestamp, message);
-    
-    if let Ok(mut logs) = logs.lock() {
-        logs.push(log_entry.clone());
-        if logs.len() > 500 {
-            logs.remove(0);
+// One SSE event per log line, as {"level": .., "line": ..} like /logs. A client too slow to
+// keep up skips ahead rather than holding the buffer back, the same as the paper WebSocket
+fn log_events(events: broadcast::Receiver<LogEntry>) -> impl futures::Stream<Item = Result<warp::sse::Event, Infallible>> {
+    futures::stream::unfold(events, |mut events| async move {
+        loop {
+            match events.recv().await {
+                Ok(entry) => {
+                    let json = serde_json::to_string(&entry).unwrap_or_default();
+                    return Some((Ok(warp::sse::Event::default().data(json)), events));
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
         }
+    })
+}
+
+// "[12:00:01] WARN  Instance ... failed", the level spelled out since there are no colours
+fn logs_as_text(entries: &[LogEntry]) -> String {
+    entries.iter()
+        .map(|entry| match entry.line.split_once("] ") {
+            Some((time, message)) => format!("{}] {:<5} {}\n", time, format!("{:?}", entry.level).to_uppercase(), message),
+            None => format!("{}\n", entry.line),
+        })
+        .collect()
+}
+
+fn add_log(logs: &Arc<LogBuffer>, level: LogLevel, message: &str) {
+    if !logs::enabled(level) {
+        return;
     }
-    
-    println!("{}", log_entry);
+    println!("[{}] {}", Local::now().format("%H:%M:%S"), message);
+    logs.push(level, message);
 }
 
-async fn clear_all_results() -> Result<impl Reply, warp::Rejection> {
-    let filepath = "results.txt";
+async fn clear_all_results(db_path: String, output: String) -> Result<impl Reply, warp::Rejection> {
+    let cleared = if db_path.is_empty() {
+        fs::write(&output, "").is_ok()
+    } else {
+        ResultsDb::open(&db_path).and_then(|db| db.clear()).is_ok()
+    };
     
-    if let Err(_) = fs::write(filepath, "") {
+    if !cleared {
         return Ok(warp::reply::json(&StatusMessage {
             status: "error".to_string(),
             message: "Could not clear results".to_string(),
@@ -264,6 +433,32 @@ async fn clear_all_results() -> Result<impl Reply, warp::Rejection> {
     }))
 }
 
+// One paper out of the results, by DOI. Papers saved without one have nothing to pick them by
+async fn delete_result(request: DeleteRequest, db_path: String, output: String) -> Result<impl Reply, warp::Rejection> {
+    let doi = request.doi.trim();
+    if doi.is_empty() || doi == "NA" {
+        return Ok(warp::reply::json(&StatusMessage {
+            status: "error".to_string(),
+            message: "Only papers with a DOI can be deleted".to_string(),
+        }));
+    }
+
+    let deleted = if db_path.is_empty() {
+        // A search saving into the file meanwhile would be overwritten by the rewrite
+        let _guard = crate::WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        results::remove_doi(&output, doi)
+    } else {
+        ResultsDb::open(&db_path).and_then(|db| Ok((db.delete(doi)?, db.count()?)))
+    };
+
+    let message = match deleted {
+        Ok((0, _)) => StatusMessage { status: "error".to_string(), message: format!("No result with DOI {}", doi) },
+        Ok((_, left)) => StatusMessage { status: "ok".to_string(), message: format!("Deleted {}, {} results left", doi, left) },
+        Err(e) => StatusMessage { status: "error".to_string(), message: format!("Could not delete {}: {}", doi, e) },
+    };
+    Ok(warp::reply::json(&message))
+}
+
 async fn validate_service(request: ValidateRequest) -> Result<impl Reply, warp::Rejection> {
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(5))
@@ -367,18 +562,22 @@ fn index_html() -> String {
 
         button { padding: 8px 16px; background: rgb(100, 149, 237); color: white; border: none; cursor: pointer; margin-right: 5px; border-radius: 0; }
         button:hover { background: #5a8dd4; }
+        button:disabled { background: #999; cursor: not-allowed; }
         button.danger { background: #dc3545; }
         button.danger:hover { background: #c82333; }
         button.validate { background: #28a745; font-size: 12px; padding: 4px 8px; }
         button.validate:hover { background: #218838; }
 
         .result { background: white; padding: 15px; margin: 10px 0; border: 1px solid #ddd; border-radius: 0; position: relative; }
+        .result .delete-button { position: absolute; top: 10px; right: 10px; font-size: 12px; padding: 4px 8px; }
         .result h3 { margin: 0 0 10px 0; }
 
         .result a { color: #007bff; text-decoration: none; }
         .result a:hover { text-decoration: underline; }
 
         .score { font-weight: bold; color: rgb(0, 150, 255); }
+        .citations { color: #666; margin-left: 10px; }
+        .authors { color: #555; font-size: 13px; margin: 2px 0 6px; }
         .info { color: #666; font-size: 14px; }
 
         .doi-badge { background: #28a745; color: white; padding: 3px 8px; border-radius: 0; font-size: 12px; font-family: monospace; }
@@ -410,6 +609,8 @@ fn index_html() -> String {
 
         .loading { display: none; padding: 10px; background: #fff3cd; border: 1px solid #ffc107; border-radius: 0; margin: 10px 0; }
         .loading.active { display: block; }
+        .progress { height: 8px; background: #ffe8a1; margin-top: 8px; }
+        .progress-bar { height: 100%; width: 0; background: #ffc107; transition: width 0.3s; }
 
         .log-container {
             background: #1e1e1e;
@@ -424,6 +625,9 @@ fn index_html() -> String {
         }
 
         .log-entry { margin: 3px 0; }
+        .log-error { color: #f48771; }
+        .log-warn { color: #dcdcaa; }
+        .log-debug { color: #808080; }
 
         .validation-status { display: inline-block; padding: 2px 6px; border-radius: 0; font-size: 11px; margin-left: 10px; }
         .validation-status.success { background: #d4edda; color: #155724; }
@@ -436,7 +640,10 @@ fn index_html() -> String {
         <h1>Researcher</h1>
 
         <div id="status-message" class="status-message"></div>
-        <div id="loading" class="loading">Searching and validating...</div>
+        <div id="loading" class="loading">
+            <span id="progress-text">Searching and validating...</span>
+            <div class="progress"><div id="progress-bar" class="progress-bar"></div></div>
+        </div>
         
         <div class="tabs">
             <div class="tab active" onclick="showTab(event, 'search')">Search</div>
@@ -449,7 +656,7 @@ fn index_html() -> String {
                 <h2>Configure Search</h2>
                 
                 <label>Subject:</label>
-                <input type="text" id="subject" value="machine learning" placeholder="e.g. quantum computing">
+                <input type="text" id="subject" value="machine learning" placeholder="e.g. quantum computing, or several separated by commas">
                 
                 <div class="form-row-with-button">
                     <div>
@@ -511,7 +718,7 @@ fn index_html() -> String {
                 <input type="text" id="engines" value="arxiv,pubmed,google scholar,crossref,openairepublications,openairedatasets,semantic scholar">
                 
                 <br><br>
-                <button onclick="startSearch()">Start Search</button>
+                <button id="start_button" onclick="startSearch()">Start Search</button>
             </div>
         </div>
         
@@ -520,14 +727,28 @@ fn index_html() -> String {
                 <input type="text" id="search_term" placeholder="Search in results..." style="width: 400px;">
                 <button onclick="searchResults()">Search</button>
                 <button onclick="loadResults()">Show All</button>
+                <select id="sort_by" onchange="searchResults()">
+                    <option value="">Saved</option>
+                    <option value="score">Score</option>
+                    <option value="citations">Citations</option>
+                    <option value="date">Published</option>
+                    <option value="title">Title</option>
+                </select>
+                <select id="sort_order" onchange="searchResults()">
+                    <option value="desc">Descending</option>
+                    <option value="asc">Ascending</option>
+                </select>
                 <button class="danger" onclick="clearAllResults()">Clear All Results</button>
             </div>
+            <p id="results-count"></p>
             <div id="results"></div>
+            <button id="load-more" onclick="fetchResults(true)" style="display: none;">Load more</button>
         </div>
         
         <div id="logs-tab" class="tab-content">
             <h2>Technical Logs</h2>
             <button onclick="loadLogs()">Refresh Logs</button>
+            <button onclick="window.location.href = '/logs/download'">Download Logs</button>
             <button class="danger" onclick="clearLogs()">Clear Display</button>
             <div class="log-container" id="log-container"></div>
         </div>
@@ -539,7 +760,7 @@ fn index_html() -> String {
     
     <script>
         // I hate JS
-        let logInterval;
+        let logStream;
         
         function showStatusMessage(message, isSuccess) {
             const element = document.getElementById('status-message');
@@ -564,9 +785,9 @@ fn index_html() -> String {
             document.getElementById(tabId + '-tab').classList.add('active');
 
             if (tabId === 'logs') {
-                startLogPolling();
+                startLogStream();
             } else {
-                stopLogPolling();
+                stopLogStream();
             }
         }
 
@@ -653,11 +874,16 @@ fn index_html() -> String {
             })
             .then(r => r.json())
             .then(data => {
-                showStatusMessage(data.message + ' Check the Logs tab for progress.', data.status === 'ok');
-                
-                setTimeout(() => {
+                if (data.status === 'busy') {
+                    showStatusMessage(data.message, false);
                     document.getElementById('loading').classList.remove('active');
-                }, 3000);
+                    checkBusy();
+                    return;
+                }
+                
+                showStatusMessage(data.message + ' Check the Logs tab for progress.', data.status === 'ok');
+                checkBusy();
+                checkStatus();
                 
                 setTimeout(() => {
                     document.querySelectorAll('.tab')[2].click();
@@ -669,14 +895,79 @@ fn index_html() -> String {
             });
         }
         
-        function startLogPolling() {
+        // Shows the progress box for as long as a search runs, also after a page reload
+        function checkStatus() {
+            fetch('/status')
+                .then(r => r.json())
+                .then(status => {
+                    const loading = document.getElementById('loading');
+                    if (!status.running) {
+                        loading.classList.remove('active');
+                        return;
+                    }
+                    loading.classList.add('active');
+                    const text = document.getElementById('progress-text');
+                    const bar = document.getElementById('progress-bar');
+                    if (status.total === 0) {
+                        text.textContent = 'Searching...';
+                        bar.style.width = '0';
+                    } else {
+                        text.textContent = `Processed ${status.processed} of ${status.total} (saved ${status.saved}, skipped ${status.skipped})`;
+                        bar.style.width = Math.min(100, status.processed / status.total * 100) + '%';
+                    }
+                })
+                .catch(() => {});
+        }
+        
+        function checkBusy() {
+            fetch('/busy')
+                .then(r => r.json())
+                .then(data => {
+                    const button = document.getElementById('start_button');
+                    button.disabled = data.busy;
+                    button.textContent = data.busy ? 'Search running...' : 'Start Search';
+                })
+                .catch(() => {});
+        }
+        
+        // The backlog once from /logs, then each new line as the server sends it
+        function startLogStream() {
+            stopLogStream();
             loadLogs();
-            logInterval = setInterval(loadLogs, 2000);
+            logStream = new EventSource('/logs/stream');
+            logStream.onmessage = event => appendLog(JSON.parse(event.data));
         }
         
-        function stopLogPolling() {
-            if (logInterval) {
-                clearInterval(logInterval);
+        function stopLogStream() {
+            if (logStream) {
+                logStream.close();
+                logStream = null;
+            }
+        }
+        
+        function logElement(entry) {
+            const div = document.createElement('div');
+            div.className = 'log-entry log-' + entry.level;
+            div.textContent = entry.line;
+            return div;
+        }
+        
+        function appendLog(entry) {
+            const container = document.getElementById('log-container');
+            const wasScrolledToBottom = container.scrollHeight - container.scrollTop <= container.clientHeight + 1;
+            
+            const placeholder = container.querySelector('.log-placeholder');
+            if (placeholder) {
+                placeholder.remove();
+            }
+            container.appendChild(logElement(entry));
+            
+            // The server keeps 500 lines, so does the page
+            while (container.children.length > 500) {
+                container.removeChild(container.firstChild);
+            }
+            if (wasScrolledToBottom) {
+                container.scrollTop = container.scrollHeight;
             }
         }
         
@@ -690,16 +981,11 @@ fn index_html() -> String {
                     container.innerHTML = '';
                     
                     if (logs.length === 0) {
-                        container.innerHTML = '<div class="log-entry">No logs yet. Start a search to see activity.</div>';
+                        container.innerHTML = '<div class="log-entry log-placeholder">No logs yet. Start a search to see activity.</div>';
                         return;
                     }
                     
-                    logs.forEach(log => {
-                        const div = document.createElement('div');
-                        div.className = 'log-entry';
-                        div.textContent = log;
-                        container.appendChild(div);
-                    });
+                    logs.forEach(entry => container.appendChild(logElement(entry)));
                     
                     if (wasScrolledToBottom || container.scrollTop === 0) {
                         container.scrollTop = container.scrollHeight;
@@ -711,76 +997,137 @@ fn index_html() -> String {
             document.getElementById('log-container').innerHTML = '<div class="log-entry">Logs cleared (display only, server logs still active)</div>';
         }
         
-        function loadResults() {
-            fetch('/results')
+        function paperElement(paper) {
+            const div = document.createElement('div');
+            div.className = 'result';
+            
+            // Array.from splits by code point, substring could cut an emoji or CJK surrogate pair in half
+            const abstractChars = Array.from(paper.abstract_text);
+            const abstractPreview = abstractChars.length > 300
+                ? abstractChars.slice(0, 300).join('') + '...'
+                : paper.abstract_text;
+            // Long author lists are cut to the first three, the full list is in the results file
+            const authors = paper.authors || [];
+            const authorLine = authors.length > 3 ? authors.slice(0, 3).join(', ') + ' et al.' : authors.join(', ');
+            
+            div.innerHTML = `
+                <h3><a href="${paper.url}" target="_blank">${paper.title}</a></h3>
+                ${authorLine ? `<div class="authors">${authorLine}</div>` : ''}
+                <div class="info">
+                    <span class="doi-badge">${paper.doi}</span>
+                    <span class="score">Score: ${paper.score.toFixed(2)}/1.0</span>
+                    ${paper.cited_by != null ? `<span class="citations">Cited by ${paper.cited_by}</span>` : ''}
+                    ${paper.published ? `<span class="citations">Published ${paper.published}</span>` : ''}
+                    ${paper.resource_type ? `<span class="citations">${paper.resource_type}</span>` : ''}
+                    ${paper.subject ? `<span class="citations">${paper.subject}</span>` : ''}
+                    ${paper.oa_url ? `<a class="citations" href="${paper.oa_url}" target="_blank">Open access</a>` : ''}
+                    <span style="float: right;">${paper.timestamp}</span>
+                </div>
+                <div class="abstract">${abstractPreview}</div>
+            `;
+            
+            // DOI-less papers can't be told apart for deleting
+            if (paper.doi && paper.doi !== 'NA') {
+                const button = document.createElement('button');
+                button.className = 'danger delete-button';
+                button.title = 'Delete this result';
+                button.textContent = '🗑';
+                button.onclick = () => deleteResult(paper.doi, div);
+                div.appendChild(button);
+            }
+            
+            return div;
+        }
+        
+        // Newly saved papers show up live, the Results tab's refetch still works without it
+        function connectPaperSocket() {
+            const protocol = location.protocol === 'https:' ? 'wss://' : 'ws://';
+            const socket = new WebSocket(protocol + location.host + '/ws');
+            
+            socket.onmessage = event => {
+                const container = document.getElementById('results');
+                const placeholder = container.querySelector('p');
+                if (placeholder) {
+                    placeholder.remove();
+                }
+                container.prepend(paperElement(JSON.parse(event.data)));
+            };
+            
+            socket.onclose = () => setTimeout(connectPaperSocket, 5000);
+        }
+        
+        // Results come in pages, "Load more" asks for the next one
+        const PAGE_SIZE = 20;
+        let shownResults = 0;
+        
+        function fetchResults(append) {
+            const searchTerm = document.getElementById('search_term').value.trim();
+            const params = new URLSearchParams({
+                sort: document.getElementById('sort_by').value,
+                order: document.getElementById('sort_order').value,
+                limit: PAGE_SIZE,
+                offset: append ? shownResults : 0
+            });
+            if (searchTerm) {
+                params.set('q', searchTerm);
+            }
+            
+            fetch('/results?' + params)
                 .then(r => r.json())
                 .then(data => {
                     const container = document.getElementById('results');
-                    container.innerHTML = '';
+                    if (!append) {
+                        container.innerHTML = '';
+                        shownResults = 0;
+                    }
                     
-                    if (data.length === 0) {
-                        container.innerHTML = '<p>No results found. Start a new search!</p>';
-                        return;
+                    if (data.total === 0) {
+                        container.innerHTML = searchTerm
+                            ? '<p>No results found for this search, maybe check the SearXNG Instance settings.</p>'
+                            : '<p>No results found. Start a new search!</p>';
                     }
                     
-                    data.forEach(paper => {
-                        const div = document.createElement('div');
-                        div.className = 'result';
-                        
-                        const abstractPreview = paper.abstract_text.length > 300
-                            ? paper.abstract_text.substring(0, 300) + '...'
-                            : paper.abstract_text;
-                        
-                        div.innerHTML = `
-                            <h3><a href="${paper.url}" target="_blank">${paper.title}</a></h3>
-                            <div class="info">
-                                <span class="doi-badge">${paper.doi}</span>
-                                <span class="score">Score: ${paper.score.toFixed(2)}/1.0</span>
-                                <span style="float: right;">${paper.timestamp}</span>
-                            </div>
-                            <div class="abstract">${abstractPreview}</div>
-                        `;
-                        
-                        container.appendChild(div);
+                    data.results.forEach(paper => {
+                        container.appendChild(paperElement(paper));
                     });
+                    shownResults += data.results.length;
+                    // No total when the server stopped reading at this page
+                    const of = data.total === null ? '' : ` of ${data.total}`;
+                    document.getElementById('results-count').textContent = shownResults > 0 ? `Showing ${shownResults}${of}` : '';
+                    document.getElementById('load-more').style.display = data.more ? 'inline-block' : 'none';
                 });
         }
+        
+        function loadResults() {
+            document.getElementById('search_term').value = '';
+            fetchResults(false);
+        }
 
     function searchResults() {
-        const searchTerm = document.getElementById('search_term').value;
-        fetch('/results?q=' + encodeURIComponent(searchTerm))
-            .then(r => r.json())
-            .then(data => {
-                const container = document.getElementById('results');
-                container.innerHTML = '';
-
-                if (data.length === 0) {
-                    container.innerHTML = '<p>No results found for this search, maybe check the SearXNG Instance settings.</p>';
-                    return;
-                }
-
-                data.forEach(paper => {
-                    const div = document.createElement('div');
-                    div.className = 'result';
-
-                    const abstractPreview = paper.abstract_text.length > 300 
-                        ? paper.abstract_text.substring(0, 300) + '...'
-                        : paper.abstract_text;
-
-                    div.innerHTML = `
-                        <h3><a href="${paper.url}" target="_blank">${paper.title}</a></h3>
-                        <div class="info">
-                            <span class="doi-badge">${paper.doi}</span>
-                            <span class="score">Score: ${paper.score.toFixed(2)}/1.0</span>
-                            <span style="float: right;">${paper.timestamp}</span>
-                        </div>
-                        <div class="abstract">${abstractPreview}</div>
-                    `;
-
-                    container.appendChild(div);
-                });
-            });
+        fetchResults(false);
     }
+    function deleteResult(doi, element) {
+        if (!confirm('Delete ' + doi + ' from the results?')) {
+            return;
+        }
+        
+        fetch('/delete', {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ doi: doi })
+        })
+        .then(r => r.json())
+        .then(data => {
+            showStatusMessage(data.message, data.status === 'ok');
+            if (data.status === 'ok') {
+                element.remove();
+            }
+        })
+        .catch(err => {
+            showStatusMessage('Something went wrong: ' + err, false);
+        });
+    }
+    
     function clearAllResults() {
         if (!confirm('Are you sure you want to clear all results forever?')) {
             return;
@@ -803,6 +1150,11 @@ fn index_html() -> String {
     }
     
     loadResults();
+    connectPaperSocket();
+    checkBusy();
+    checkStatus();
+    setInterval(checkBusy, 3000);
+    setInterval(checkStatus, 1000);
 </script>
 </body>
 </html>"#.to_string()
@@ -0,0 +1,165 @@
+// End-to-end runs against stand-ins for SearXNG, the DOI registries and Ollama, checking what
+// ends up in the output file. The search result only quotes its DOI in the snippet, so the run
+// has to go through the landing page (404 here) and the DOI APIs like it would for a real paper.
+// doi.org and DataCite point at the CrossRef stand-in too, where only CrossRef's path answers,
+// so nothing leaves the machine.
+
+use clap::Parser;
+use researcher::{Args, DOIScraper};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const DOI: &str = "10.99999/researcher.mock.1";
+const TITLE: &str = "Folding Proteins with Mock Servers";
+const MODEL: &str = "llama3.2:latest";
+
+// A fresh directory per test for the output, review and queue files
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("researcher-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn args(dir: &Path, searxng: &MockServer, crossref: &MockServer, ollama: &MockServer, extra: &[&str]) -> Args {
+    let file = |name: &str| dir.join(name).to_string_lossy().into_owned();
+    let mut argv: Vec<String> = [
+        "researcher",
+        "--subject", "protein folding",
+        "--instance", &searxng.uri(),
+        "--crossref-url", &crossref.uri(),
+        "--doi-org-url", &crossref.uri(),
+        "--datacite-url", &crossref.uri(),
+        "--ollama-url", &ollama.uri(),
+        "--model", MODEL,
+        "--output", &file("results.txt"),
+        "--review-output", &file("needs_review.txt"),
+        "--queue-file", &file("queue.jsonl"),
+        "--cache-dir", "",
+        "--max-results", "1",
+        "--max-pages", "1",
+        "--max-retries", "0",
+        "--api-interval-ms", "0",
+        "--rate-limit-ms", "0",
+    ].iter().map(|a| a.to_string()).collect();
+    argv.extend(extra.iter().map(|a| a.to_string()));
+    Args::parse_from(argv)
+}
+
+async fn searxng() -> MockServer {
+    let server = MockServer::start().await;
+    // The landing page is on the same server and isn't mocked, it answers 404
+    let results = json!({
+        "results": [{
+            "title": "Folding proteins",
+            "url": format!("{}/article/1", server.uri()),
+            "content": format!("doi: {}", DOI),
+            "engine": "crossref",
+        }]
+    });
+    Mock::given(method("GET"))
+        .and(path("/search"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(results))
+        .expect(1)
+        .mount(&server)
+        .await;
+    server
+}
+
+async fn crossref() -> MockServer {
+    let server = MockServer::start().await;
+    let work = json!({
+        "message": {
+            "DOI": DOI,
+            "title": [TITLE],
+            "abstract": "<jats:p>We show that protein folding can be predicted from sequence alone, using a model trained on every structure deposited so far.</jats:p>",
+            "type": "journal-article",
+            "author": [{ "given": "Ada", "family": "Lovelace" }],
+            "is-referenced-by-count": 42,
+        }
+    });
+    Mock::given(method("GET"))
+        .and(path(format!("/works/{}", DOI)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(work))
+        .mount(&server)
+        .await;
+    server
+}
+
+// `scored` is how many papers the model should be asked about
+async fn ollama(scored: u64) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/tags"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "models": [{ "name": MODEL, "modified_at": "2025-01-01T00:00:00Z", "size": 1 }]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "model": MODEL,
+            "created_at": "2025-01-01T00:00:00Z",
+            "response": "{\"score\": 0.9, \"reason\": \"Predicts protein folding, exactly the subject.\"}",
+            "done": true,
+        })))
+        .expect(scored)
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn saves_a_paper_the_model_accepts() {
+    let dir = scratch_dir("happy");
+    let (searxng, crossref, ollama) = (searxng().await, crossref().await, ollama(1).await);
+
+    let mut scraper = DOIScraper::new(args(&dir, &searxng, &crossref, &ollama, &[])).await.unwrap();
+    scraper.run().await.unwrap();
+
+    let output = fs::read_to_string(dir.join("results.txt")).unwrap();
+    assert!(output.contains(&format!("DOI: {}", DOI)), "{}", output);
+    assert!(output.contains(&format!("Title: {}", TITLE)), "{}", output);
+    assert!(output.contains("Authors: Ada Lovelace"), "{}", output);
+    assert!(output.contains("Score: 0.90"), "{}", output);
+    assert!(output.contains("Reason: Predicts protein folding, exactly the subject."), "{}", output);
+    assert!(output.contains("Citations: 42"), "{}", output);
+}
+
+#[tokio::test]
+async fn saves_without_asking_the_model_with_no_ai() {
+    let dir = scratch_dir("no-ai");
+    let (searxng, crossref, ollama) = (searxng().await, crossref().await, ollama(0).await);
+
+    let mut scraper = DOIScraper::new(args(&dir, &searxng, &crossref, &ollama, &["--no-ai"])).await.unwrap();
+    scraper.run().await.unwrap();
+
+    let output = fs::read_to_string(dir.join("results.txt")).unwrap();
+    assert!(output.contains(&format!("Title: {}", TITLE)), "{}", output);
+    assert!(output.contains("Score: 0.80"), "{}", output);
+    assert!(output.contains("Reason: AI disabled"), "{}", output);
+}
+
+#[tokio::test]
+async fn fails_the_run_when_searxng_errors() {
+    let dir = scratch_dir("searxng-500");
+    let searxng = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/search"))
+        .respond_with(ResponseTemplate::new(500).set_body_string("Internal Server Error"))
+        .expect(1)
+        .mount(&searxng)
+        .await;
+    let (crossref, ollama) = (crossref().await, ollama(0).await);
+
+    let mut scraper = DOIScraper::new(args(&dir, &searxng, &crossref, &ollama, &[])).await.unwrap();
+    let error = scraper.run().await.unwrap_err();
+
+    assert!(error.to_string().contains("500"), "{}", error);
+    let output = fs::read_to_string(dir.join("results.txt")).unwrap_or_default();
+    assert!(output.trim().is_empty(), "{}", output);
+}